@@ -0,0 +1,326 @@
+//! What is left on a board: its objects, sorted by kind.
+//!
+//! The board is split into groups of touching alive cellules. Each group is
+//! brought into a canonical form, the smallest of its 8 rotations and
+//! reflections moved to the origin, and looked up among the common objects.
+//! Every phase of the oscillators and spaceships is in the lookup, generated
+//! by running one phase of each.
+//!
+//! Some phases of the toad, the beacon and the spaceships come apart into
+//! pieces one dead cellule apart, so the groups that are not found are
+//! grouped again with cellules that far apart in the same group, and looked
+//! up once more. Objects found on their own, like two blocks side by side,
+//! are counted apart however close they are.
+//!
+//! This only makes sense for plain Life. Unknown cellules with less than two
+//! dead cellules between them are counted as one unknown object.
+
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use super::sparse::SparseLife;
+use super::Engine;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Unknown,
+}
+
+/// How many of one object are on the board.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CensusEntry {
+    pub name: String,
+    pub kind: ObjectKind,
+    pub count: usize,
+}
+
+type Shape = Vec<(i64, i64)>;
+type Transform = fn((i64, i64)) -> (i64, i64);
+
+/// Common objects: name, kind, period and one phase, row by row.
+const OBJECTS: [(&str, ObjectKind, usize, &str); 14] = [
+    ("Block", ObjectKind::StillLife, 1, "OO/OO"),
+    ("Beehive", ObjectKind::StillLife, 1, ".OO./O..O/.OO."),
+    ("Loaf", ObjectKind::StillLife, 1, ".OO./O..O/.O.O/..O."),
+    ("Boat", ObjectKind::StillLife, 1, "OO./O.O/.O."),
+    ("Ship", ObjectKind::StillLife, 1, "OO./O.O/.OO"),
+    ("Tub", ObjectKind::StillLife, 1, ".O./O.O/.O."),
+    ("Pond", ObjectKind::StillLife, 1, ".OO./O..O/O..O/.OO."),
+    ("Barge", ObjectKind::StillLife, 1, ".O../O.O./.O.O/..O."),
+    ("Long Boat", ObjectKind::StillLife, 1, "OO../O.O./.O.O/..O."),
+    ("Blinker", ObjectKind::Oscillator, 2, "OOO"),
+    ("Toad", ObjectKind::Oscillator, 2, ".OOO/OOO."),
+    ("Beacon", ObjectKind::Oscillator, 2, "OO../OO../..OO/..OO"),
+    ("Glider", ObjectKind::Spaceship, 4, ".O./..O/OOO"),
+    (
+        "Lightweight Spaceship",
+        ObjectKind::Spaceship,
+        4,
+        ".O..O/O..../O...O/OOOO.",
+    ),
+];
+
+/// The objects on a board, most common first.
+pub fn take_census(width: usize, height: usize, alive_cellules: &[bool]) -> Vec<CensusEntry> {
+    let lookup = lookup();
+    let mut counts: HashMap<(String, ObjectKind), usize> = HashMap::new();
+
+    let mut unknown_cellules = vec![false; alive_cellules.len()];
+    for group in groups(width, height, alive_cellules, 1) {
+        match lookup.get(&canonical(&group)) {
+            Some((name, kind)) => *counts.entry((name.to_string(), *kind)).or_insert(0) += 1,
+            None => {
+                for (x, y) in group {
+                    unknown_cellules[y as usize * width + x as usize] = true;
+                }
+            }
+        }
+    }
+
+    for group in groups(width, height, &unknown_cellules, 2) {
+        let key = match lookup.get(&canonical(&group)) {
+            Some((name, kind)) => (name.to_string(), *kind),
+            None => (
+                format!("Unknown ({} cellules)", group.len()),
+                ObjectKind::Unknown,
+            ),
+        };
+        *counts.entry(key).or_insert(0) += 1;
+    }
+
+    let mut entries = counts
+        .into_iter()
+        .map(|((name, kind), count)| CensusEntry { name, kind, count })
+        .collect::<Vec<CensusEntry>>();
+    entries.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+    entries
+}
+
+/// Groups of alive cellules at most `reach` cellules apart in both
+/// directions, as coordinates: touching with 1, at most one dead cellule
+/// apart with 2.
+fn groups(width: usize, height: usize, alive_cellules: &[bool], reach: i64) -> Vec<Shape> {
+    let mut seen = vec![false; alive_cellules.len()];
+    let mut groups = vec![];
+
+    for start in 0..alive_cellules.len() {
+        if !alive_cellules[start] || seen[start] {
+            continue;
+        }
+
+        seen[start] = true;
+        let mut pending = vec![start];
+        let mut group = vec![];
+        while let Some(index) = pending.pop() {
+            let (x, y) = ((index % width) as i64, (index / width) as i64);
+            group.push((x, y));
+
+            for neighbor_y in (y - reach)..=(y + reach) {
+                for neighbor_x in (x - reach)..=(x + reach) {
+                    if neighbor_x < 0
+                        || neighbor_y < 0
+                        || neighbor_x >= width as i64
+                        || neighbor_y >= height as i64
+                    {
+                        continue;
+                    }
+                    let neighbor = neighbor_y as usize * width + neighbor_x as usize;
+                    if alive_cellules[neighbor] && !seen[neighbor] {
+                        seen[neighbor] = true;
+                        pending.push(neighbor);
+                    }
+                }
+            }
+        }
+        groups.push(group);
+    }
+
+    groups
+}
+
+/// The smallest of the shape's 8 rotations and reflections, moved so its
+/// bounding box starts at the origin.
+fn canonical(shape: &[(i64, i64)]) -> Shape {
+    let transforms: [Transform; 8] = [
+        |(x, y)| (x, y),
+        |(x, y)| (-x, y),
+        |(x, y)| (x, -y),
+        |(x, y)| (-x, -y),
+        |(x, y)| (y, x),
+        |(x, y)| (-y, x),
+        |(x, y)| (y, -x),
+        |(x, y)| (-y, -x),
+    ];
+
+    transforms
+        .iter()
+        .map(|transform| {
+            let moved = shape.iter().map(|cell| transform(*cell)).collect::<Shape>();
+            let min_x = moved.iter().map(|(x, _)| *x).min().unwrap_or(0);
+            let min_y = moved.iter().map(|(_, y)| *y).min().unwrap_or(0);
+            let mut normalized = moved
+                .into_iter()
+                .map(|(x, y)| (x - min_x, y - min_y))
+                .collect::<Shape>();
+            normalized.sort_unstable();
+            normalized
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Canonical forms of every phase of the common objects.
+fn lookup() -> HashMap<Shape, (&'static str, ObjectKind)> {
+    let mut lookup = HashMap::new();
+
+    for &(name, kind, period, pattern) in OBJECTS.iter() {
+        for phase in phases(pattern, period) {
+            lookup.insert(canonical(&phase), (name, kind));
+        }
+    }
+
+    lookup
+}
+
+/// The pattern and the generations after it, `period` shapes in all.
+fn phases(pattern: &str, period: usize) -> Vec<Shape> {
+    let rows = pattern.split('/').collect::<Vec<&str>>();
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let alive = rows
+        .iter()
+        .flat_map(|row| (0..width).map(move |x| row.as_bytes().get(x) == Some(&b'O')))
+        .collect::<Vec<bool>>();
+
+    let mut life = SparseLife::new(width, rows.len(), &alive);
+    let mut phases = vec![];
+    for _ in 0..period {
+        let bounds = life.bounding_box().unwrap();
+        let cellules = life.cellules_in(&bounds);
+        phases.push(
+            cellules
+                .iter()
+                .enumerate()
+                .filter(|(_, alive)| **alive)
+                .map(|(index, _)| {
+                    (
+                        (index % bounds.width()) as i64,
+                        (index / bounds.width()) as i64,
+                    )
+                })
+                .collect(),
+        );
+        life.step();
+    }
+
+    phases
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    const WIDTH: usize = 20;
+    const HEIGHT: usize = 12;
+
+    /// A board with `pattern` drawn at each offset.
+    fn board(patterns: &[(&str, usize, usize)]) -> Vec<bool> {
+        let mut cellules = vec![false; WIDTH * HEIGHT];
+        for (pattern, left, top) in patterns {
+            for (y, row) in pattern.split('/').enumerate() {
+                for (x, cell) in row.chars().enumerate() {
+                    if cell == 'O' {
+                        cellules[(top + y) * WIDTH + left + x] = true;
+                    }
+                }
+            }
+        }
+        cellules
+    }
+
+    fn counts(census: &[CensusEntry]) -> Vec<(&str, ObjectKind, usize)> {
+        census
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.kind, entry.count))
+            .collect()
+    }
+
+    #[test]
+    fn test_canonical_form_ignores_position_and_symmetry() {
+        let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mirrored = vec![(11, 5), (10, 6), (12, 7), (11, 7), (10, 7)];
+        let rotated = vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+        assert_eq!(canonical(&glider), canonical(&mirrored));
+        assert_eq!(canonical(&glider), canonical(&rotated));
+        assert_ne!(canonical(&glider), canonical(&[(0, 0), (1, 0), (2, 0)]));
+    }
+
+    #[test]
+    fn test_every_phase_is_counted() {
+        for &(name, kind, period, pattern) in OBJECTS.iter() {
+            for phase in phases(pattern, period) {
+                let mut cellules = vec![false; WIDTH * HEIGHT];
+                for (x, y) in phase {
+                    cellules[(y as usize + 3) * WIDTH + x as usize + 3] = true;
+                }
+
+                assert_eq!(
+                    counts(&take_census(WIDTH, HEIGHT, &cellules)),
+                    vec![(name, kind, 1)]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_census() {
+        let cellules = board(&[
+            ("OO/OO", 0, 0),
+            ("OO/OO", 5, 0),
+            (".O/O.O/.O", 10, 0),
+            ("O/O/O", 16, 1),
+            ("O.O/.OO/.O.", 0, 6),
+            ("OO../OO../..OO", 6, 6),
+            ("OOOO", 14, 8),
+        ]);
+
+        assert_eq!(
+            counts(&take_census(WIDTH, HEIGHT, &cellules)),
+            vec![
+                ("Block", ObjectKind::StillLife, 2),
+                ("Blinker", ObjectKind::Oscillator, 1),
+                ("Glider", ObjectKind::Spaceship, 1),
+                ("Tub", ObjectKind::StillLife, 1),
+                ("Unknown (4 cellules)", ObjectKind::Unknown, 1),
+                ("Unknown (6 cellules)", ObjectKind::Unknown, 1),
+            ]
+        );
+        assert!(take_census(WIDTH, HEIGHT, &vec![false; WIDTH * HEIGHT]).is_empty());
+    }
+
+    #[test]
+    fn test_close_objects_are_counted_apart() {
+        let cellules = board(&[
+            ("OO/OO", 0, 0),
+            ("OO/OO", 3, 0),
+            ("OO/OO", 10, 0),
+            ("O/O/O", 13, 0),
+            (".OOO/OOO.", 0, 6),
+            ("OO/OO", 5, 6),
+        ]);
+
+        assert_eq!(
+            counts(&take_census(WIDTH, HEIGHT, &cellules)),
+            vec![
+                ("Block", ObjectKind::StillLife, 4),
+                ("Blinker", ObjectKind::Oscillator, 1),
+                ("Toad", ObjectKind::Oscillator, 1),
+            ]
+        );
+    }
+}
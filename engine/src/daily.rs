@@ -0,0 +1,197 @@
+use game_of_life_core::core::seeds::seeds::{get_seeds, Seed};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::geometry::Geometry;
+use super::rules::Rule;
+use super::{to_cellules, Topology};
+
+/// Edits allowed on the daily board.
+pub const DAILY_EDIT_BUDGET: usize = 5;
+
+const LABEL_PREFIX: &str = "Daily ";
+const BOARD_WIDTH: usize = 50;
+const BOARD_HEIGHT: usize = 40;
+const SOUP_WIDTH: usize = 20;
+const SOUP_HEIGHT: usize = 14;
+const SOUP_DENSITY: f64 = 0.4;
+
+/// The Daily Challenge: one random board per UTC day, the same for everyone.
+///
+/// The board is a random soup in the middle of the usual 50 by 40 board,
+/// drawn from a PRNG seeded with the date. Nothing else goes into it, so the
+/// server and the verifier rebuild it from the seed label alone. The label
+/// is `Daily YYYY-MM-DD`. The challenge is always plain Life on the bounded
+/// square board, with at most `DAILY_EDIT_BUDGET` edits. Challenges order
+/// by date.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DailyChallenge {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl DailyChallenge {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<DailyChallenge> {
+        if (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month) {
+            Some(DailyChallenge { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    /// The challenge of the UTC day `days` days after 1970-01-01.
+    pub fn from_unix_days(days: i64) -> DailyChallenge {
+        // Counts in 400 year eras starting on March 1st, so leap days fall
+        // at the end of a year.
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = (year_of_era + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+
+        DailyChallenge { year, month, day }
+    }
+
+    /// The challenge a seed label names, if it names one. Only the label
+    /// `label` writes counts, so every challenge has exactly one.
+    pub fn parse(label: &str) -> Option<DailyChallenge> {
+        let mut parts = label.strip_prefix(LABEL_PREFIX)?.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+
+        DailyChallenge::new(year, month, day).filter(|daily| daily.label() == label)
+    }
+
+    pub fn label(&self) -> String {
+        format!(
+            "{}{:04}-{:02}-{:02}",
+            LABEL_PREFIX, self.year, self.month, self.day
+        )
+    }
+
+    pub fn rule(&self) -> Rule {
+        Rule::conway()
+    }
+
+    pub fn topology(&self) -> Topology {
+        Topology::Bounded
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        Geometry::Square
+    }
+
+    pub fn edit_budget(&self) -> usize {
+        DAILY_EDIT_BUDGET
+    }
+
+    pub fn seed(&self) -> Seed {
+        let template = &get_seeds()[0].cellules[0];
+
+        Seed {
+            label: self.label(),
+            cellules: to_cellules(template, &self.alive_cellules()),
+        }
+    }
+
+    /// The board, row by row. `StdRng` is only stable within a `rand`
+    /// release, which is why every side builds it through this crate.
+    pub fn alive_cellules(&self) -> Vec<bool> {
+        let date = self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64;
+        let mut rng = StdRng::seed_from_u64(date);
+
+        let left = (BOARD_WIDTH - SOUP_WIDTH) / 2;
+        let top = (BOARD_HEIGHT - SOUP_HEIGHT) / 2;
+        let mut cellules = vec![false; BOARD_WIDTH * BOARD_HEIGHT];
+        for y in top..(top + SOUP_HEIGHT) {
+            for x in left..(left + SOUP_WIDTH) {
+                cellules[y * BOARD_WIDTH + x] = rng.gen_bool(SOUP_DENSITY);
+            }
+        }
+        cellules
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_labels_round_trip() {
+        let challenge = DailyChallenge::new(2024, 2, 29).unwrap();
+        assert_eq!(challenge.label(), "Daily 2024-02-29");
+        assert_eq!(DailyChallenge::parse(&challenge.label()), Some(challenge));
+
+        assert_eq!(DailyChallenge::new(2023, 2, 29), None);
+        assert_eq!(DailyChallenge::new(2024, 13, 1), None);
+        assert_eq!(DailyChallenge::parse("Daily 2024-04-31"), None);
+        assert_eq!(DailyChallenge::parse("Glider Gun"), None);
+        assert_eq!(DailyChallenge::parse("Daily tomorrow"), None);
+        assert_eq!(DailyChallenge::parse("Daily 2024-2-9"), None);
+        assert_eq!(DailyChallenge::parse("Daily +2024-02-09"), None);
+        assert_eq!(DailyChallenge::parse("Daily 2024-02-09 "), None);
+    }
+
+    #[test]
+    fn test_days_and_order() {
+        let cases = vec![
+            // (days since 1970-01-01, label)
+            (0, "Daily 1970-01-01"),
+            (11_016, "Daily 2000-02-29"),
+            (20_744, "Daily 2026-10-18"),
+            (-1, "Daily 1969-12-31"),
+        ];
+
+        for (days, label) in cases {
+            assert_eq!(DailyChallenge::from_unix_days(days).label(), label);
+        }
+
+        // Challenges after today have not happened yet.
+        let today = DailyChallenge::from_unix_days(20_744);
+        assert!(DailyChallenge::new(2026, 10, 19).unwrap() > today);
+        assert!(DailyChallenge::new(2025, 12, 31).unwrap() < today);
+    }
+
+    #[test]
+    fn test_board_depends_on_the_date_only() {
+        let today = DailyChallenge::new(2026, 10, 18).unwrap();
+        let tomorrow = DailyChallenge::new(2026, 10, 19).unwrap();
+
+        assert_eq!(today.alive_cellules(), today.alive_cellules());
+        assert_ne!(today.alive_cellules(), tomorrow.alive_cellules());
+
+        let cellules = today.alive_cellules();
+        assert_eq!(cellules.len(), BOARD_WIDTH * BOARD_HEIGHT);
+        assert!(!cellules[0] && !cellules[BOARD_WIDTH * BOARD_HEIGHT - 1]);
+        assert!(cellules.iter().any(|alive| *alive));
+    }
+
+    #[test]
+    fn test_seed_is_labelled_with_the_date() {
+        let seed = DailyChallenge::new(2026, 1, 2).unwrap().seed();
+
+        assert_eq!(seed.label, "Daily 2026-01-02");
+        assert_eq!(seed.cellules.len(), BOARD_WIDTH * BOARD_HEIGHT);
+    }
+}
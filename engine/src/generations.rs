@@ -0,0 +1,215 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use game_of_life_core::core::game::GameState;
+
+use super::geometry::Geometry;
+use super::rules::Rule;
+use super::{Engine, MultiStateEngine};
+
+const ALIVE: u8 = 1;
+
+/// Engine for Generations rules on the bounded board, in any geometry.
+///
+/// Cellules past the edge are dead, the same as `GameState`.
+pub struct GenerationsLife {
+    rule: Rule,
+    states: Vec<u8>,
+    neighbors: Vec<Vec<usize>>,
+    seen_states: HashSet<u64>,
+}
+
+impl GenerationsLife {
+    pub fn new(
+        rule: Rule,
+        geometry: Geometry,
+        width: usize,
+        height: usize,
+        alive_cellules: &[bool],
+    ) -> GenerationsLife {
+        GenerationsLife::from_states(
+            rule,
+            geometry,
+            width,
+            height,
+            alive_cellules.iter().map(|alive| *alive as u8).collect(),
+        )
+    }
+
+    /// Picks up a run mid-way, dying cellules included.
+    pub fn from_states(
+        rule: Rule,
+        geometry: Geometry,
+        width: usize,
+        height: usize,
+        states: Vec<u8>,
+    ) -> GenerationsLife {
+        GenerationsLife {
+            rule,
+            states,
+            neighbors: (0..(width * height))
+                .map(|index| geometry.neighbors(index, width, height))
+                .collect(),
+            seen_states: HashSet::new(),
+        }
+    }
+
+    pub fn from_game_state(
+        rule: Rule,
+        geometry: Geometry,
+        game_state: &GameState,
+    ) -> GenerationsLife {
+        GenerationsLife::new(
+            rule,
+            geometry,
+            game_state.cellules_width,
+            game_state.cellules_height,
+            &Engine::alive_cellules(game_state),
+        )
+    }
+
+    fn alive_neighbors(&self, index: usize) -> usize {
+        self.neighbors[index]
+            .iter()
+            .filter(|neighbor| self.states[**neighbor] == ALIVE)
+            .count()
+    }
+}
+
+impl Engine for GenerationsLife {
+    fn step(&mut self) {
+        self.states = (0..self.states.len())
+            .map(|index| {
+                self.rule
+                    .next_state(self.states[index], self.alive_neighbors(index))
+            })
+            .collect();
+    }
+
+    fn alive_cellules(&self) -> Vec<bool> {
+        self.states.iter().map(|state| *state == ALIVE).collect()
+    }
+}
+
+impl MultiStateEngine for GenerationsLife {
+    fn states(&self) -> &[u8] {
+        &self.states
+    }
+
+    fn is_in_endless_loop(&mut self) -> bool {
+        let mut hasher = DefaultHasher::new();
+        self.states.hash(&mut hasher);
+
+        !self.seen_states.insert(hasher.finish())
+    }
+
+    fn toggle_cellule(&mut self, index: usize) {
+        self.states[index] = if self.states[index] == ALIVE {
+            0
+        } else {
+            ALIVE
+        };
+        self.seen_states.clear();
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use game_of_life_core::core::seeds::seeds::get_seeds;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_two_state_rule_matches_game_state() {
+        for seed in get_seeds() {
+            let mut game_state = GameState {
+                active: false,
+                cellules: seed.cellules.clone(),
+                cellules_width: 50,
+                cellules_height: 40,
+                cellule_neighbors: HashMap::new(),
+            };
+            let mut generations =
+                GenerationsLife::from_game_state(Rule::conway(), Geometry::Square, &game_state);
+
+            for step in 1..=500 {
+                Engine::step(&mut game_state);
+                generations.step();
+
+                assert_eq!(
+                    Engine::alive_cellules(&game_state),
+                    generations.alive_cellules(),
+                    "{} diverged at step {}",
+                    seed.label,
+                    step
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cellules_decay() {
+        let mut cellules = vec![false; 5 * 5];
+        cellules[12] = true;
+        let mut generations = GenerationsLife::new(
+            Rule::parse("B2/S/C4").unwrap(),
+            Geometry::Square,
+            5,
+            5,
+            &cellules,
+        );
+
+        generations.step();
+        assert_eq!(generations.states()[12], 2);
+        generations.step();
+        assert_eq!(generations.states()[12], 3);
+        generations.step();
+        assert_eq!(generations.states()[12], 0);
+        assert_eq!(generations.population(), 0);
+    }
+
+    #[test]
+    fn test_toggles_restart_loop_detection() {
+        let mut generations = GenerationsLife::new(
+            Rule::parse("B2/S/C4").unwrap(),
+            Geometry::Square,
+            5,
+            5,
+            &[false; 25],
+        );
+        assert!(!generations.is_in_endless_loop());
+        assert!(generations.is_in_endless_loop());
+
+        generations.toggle_cellule(12);
+        assert_eq!(generations.states()[12], 1);
+        assert!(!generations.is_in_endless_loop());
+
+        generations.step();
+        generations.toggle_cellule(12);
+        assert_eq!(generations.states()[12], 1);
+        generations.toggle_cellule(12);
+        assert_eq!(generations.states()[12], 0);
+    }
+
+    #[test]
+    fn test_hexagonal_neighbors() {
+        // A cellule with exactly two alive hex neighbors is born under B2/S34.
+        let mut cellules = vec![false; 5 * 5];
+        cellules[5 + 1] = true;
+        cellules[5 + 2] = true;
+        let mut generations = GenerationsLife::new(
+            Rule::parse("B2/S34").unwrap(),
+            Geometry::Hexagonal,
+            5,
+            5,
+            &cellules,
+        );
+
+        generations.step();
+        let alive = generations.alive_cellules();
+        assert!(alive[2] && alive[2 * 5 + 2]);
+        assert!(!alive[5 + 1] && !alive[5 + 2]);
+    }
+}
@@ -0,0 +1,393 @@
+use game_of_life_core::core::seeds::seeds::{get_seeds, Seed};
+use serde_derive::{Deserialize, Serialize};
+
+use super::daily::DailyChallenge;
+use super::rules::{rule_presets, Rule};
+use super::to_cellules;
+
+const HEX_ROW_RATIO: f64 = 0.866_025_403_784_438_6; // sqrt(3) / 2
+
+/// How cellules tile the board.
+///
+/// Every geometry keeps the row-major `width * height` layout of the square
+/// board; only which cellules are neighbors and where they are drawn differs.
+/// Hexagonal boards shift odd rows half a cellule to the right. Triangular
+/// boards alternate up and down pointing cellules along each row, starting
+/// with an up pointing one in the top left corner.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Geometry {
+    #[default]
+    Square,
+    Hexagonal,
+    Triangular,
+}
+
+impl Geometry {
+    pub fn all() -> Vec<Geometry> {
+        vec![Geometry::Square, Geometry::Hexagonal, Geometry::Triangular]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Geometry::Square => "Square",
+            Geometry::Hexagonal => "Hexagonal",
+            Geometry::Triangular => "Triangular",
+        }
+    }
+
+    /// Rules that make sense for the neighbor count of this geometry.
+    pub fn rule_presets(&self) -> Vec<(&'static str, Rule)> {
+        match self {
+            Geometry::Square => rule_presets(),
+            Geometry::Hexagonal => vec![
+                ("Hex Life", Rule::parse("B2/S34").unwrap()),
+                ("Hex Decay", Rule::parse("B2/S34/C4").unwrap()),
+            ],
+            Geometry::Triangular => vec![
+                ("Tri Life", Rule::parse("B4/S345").unwrap()),
+                ("Tri Decay", Rule::parse("B4/S345/C3").unwrap()),
+            ],
+        }
+    }
+
+    pub fn default_rule(&self) -> Rule {
+        self.rule_presets()[0].1.clone()
+    }
+
+    /// The seeds offered for this geometry. The built-in seeds are drawn for
+    /// the square grid; other geometries get their own patterns.
+    pub fn seeds(&self) -> Vec<Seed> {
+        let seeds = get_seeds();
+        if *self == Geometry::Square {
+            return seeds;
+        }
+
+        let template = &seeds[0].cellules[0];
+        self.seed_patterns()
+            .into_iter()
+            .map(|(label, pattern)| Seed {
+                label: label.to_owned(),
+                cellules: to_cellules(template, &pattern_cellules(&pattern, 50, 40)),
+            })
+            .collect()
+    }
+
+    /// The seed labelled `label`, Daily Challenge boards included.
+    pub fn seed(&self, label: &str) -> Option<Seed> {
+        match DailyChallenge::parse(label) {
+            Some(daily) if *self == daily.geometry() => Some(daily.seed()),
+            _ => self.seeds().into_iter().find(|seed| seed.label == label),
+        }
+    }
+
+    /// Starting patterns for geometries the built-in seeds weren't drawn for,
+    /// as offsets from the centre of the board.
+    pub fn seed_patterns(&self) -> Vec<(&'static str, Vec<(i64, i64)>)> {
+        match self {
+            Geometry::Square => vec![],
+            Geometry::Hexagonal => vec![
+                (
+                    "Hex Flower",
+                    vec![(-1, -1), (0, -1), (1, 0), (0, 1), (-1, 1), (-1, 0)],
+                ),
+                ("Hex Line", (-4..=4).map(|x| (x, 0)).collect()),
+                (
+                    "Hex Bars",
+                    (-3..=3).flat_map(|x| vec![(x, -2), (x, 2)]).collect(),
+                ),
+            ],
+            Geometry::Triangular => vec![
+                (
+                    "Tri Hexagon",
+                    vec![(-1, 0), (0, 0), (1, 0), (-1, -1), (0, -1), (1, -1)],
+                ),
+                ("Tri Band", (-6..=6).map(|x| (x, 0)).collect()),
+                (
+                    "Tri Diamonds",
+                    vec![
+                        (-4, 0),
+                        (-3, 0),
+                        (-4, 1),
+                        (-3, 1),
+                        (3, 0),
+                        (4, 0),
+                        (3, 1),
+                        (4, 1),
+                    ],
+                ),
+            ],
+        }
+    }
+
+    pub fn neighbors(&self, index: usize, width: usize, height: usize) -> Vec<usize> {
+        let x = (index % width) as i64;
+        let y = (index / width) as i64;
+
+        self.neighbor_offsets(x, y)
+            .into_iter()
+            .map(|(offset_x, offset_y)| (x + offset_x, y + offset_y))
+            .filter(|&(neighbor_x, neighbor_y)| {
+                neighbor_x >= 0
+                    && neighbor_y >= 0
+                    && neighbor_x < width as i64
+                    && neighbor_y < height as i64
+            })
+            .map(|(neighbor_x, neighbor_y)| (neighbor_y as usize) * width + neighbor_x as usize)
+            .collect()
+    }
+
+    fn neighbor_offsets(&self, x: i64, y: i64) -> Vec<(i64, i64)> {
+        match self {
+            Geometry::Square => vec![
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+            ],
+            Geometry::Hexagonal => {
+                let shift = y.rem_euclid(2);
+                vec![
+                    (shift - 1, -1),
+                    (shift, -1),
+                    (1, 0),
+                    (shift, 1),
+                    (shift - 1, 1),
+                    (-1, 0),
+                ]
+            }
+            // Every triangle touching an edge or a corner: twelve in total.
+            Geometry::Triangular => {
+                let (wide_row, narrow_row) = if (x + y).rem_euclid(2) == 0 {
+                    (1, -1)
+                } else {
+                    (-1, 1)
+                };
+
+                let mut offsets = vec![];
+                offsets.extend((-1..=1).map(|offset_x| (offset_x, narrow_row)));
+                offsets.extend([-2, -1, 1, 2].iter().map(|offset_x| (*offset_x, 0)));
+                offsets.extend((-2..=2).map(|offset_x| (offset_x, wide_row)));
+                offsets
+            }
+        }
+    }
+
+    pub fn canvas_size(&self, width: usize, height: usize, cellule_size: f64) -> (f64, f64) {
+        match self {
+            Geometry::Square => (width as f64 * cellule_size, height as f64 * cellule_size),
+            Geometry::Hexagonal => (
+                (width as f64 + 0.5) * cellule_size,
+                ((height as f64 - 1.0) * HEX_ROW_RATIO + 1.0) * cellule_size,
+            ),
+            Geometry::Triangular => (
+                (width as f64 + 1.0) * cellule_size / 2.0,
+                height as f64 * HEX_ROW_RATIO * cellule_size,
+            ),
+        }
+    }
+
+    pub fn cellule_centre(&self, column: usize, row: usize, cellule_size: f64) -> (f64, f64) {
+        let (column, row) = (column as f64, row as f64);
+        match self {
+            Geometry::Square => ((column + 0.5) * cellule_size, (row + 0.5) * cellule_size),
+            Geometry::Hexagonal => (
+                (column + 0.5 + (row % 2.0) / 2.0) * cellule_size,
+                (row * HEX_ROW_RATIO + 0.5) * cellule_size,
+            ),
+            Geometry::Triangular => {
+                let outline = self.cellule_outline(column as usize, row as usize, cellule_size);
+                (
+                    outline.iter().map(|(x, _)| x).sum::<f64>() / 3.0,
+                    outline.iter().map(|(_, y)| y).sum::<f64>() / 3.0,
+                )
+            }
+        }
+    }
+
+    /// Corners of the cellule, clockwise. Square cellules are drawn as
+    /// circles and have no outline.
+    pub fn cellule_outline(&self, column: usize, row: usize, cellule_size: f64) -> Vec<(f64, f64)> {
+        match self {
+            Geometry::Square => vec![],
+            Geometry::Hexagonal => {
+                let (centre_x, centre_y) = self.cellule_centre(column, row, cellule_size);
+                let radius = cellule_size / 2.0 / HEX_ROW_RATIO;
+                (0..6)
+                    .map(|corner| {
+                        let angle = std::f64::consts::PI / 3.0 * (corner as f64)
+                            - std::f64::consts::PI / 2.0;
+                        (
+                            centre_x + radius * angle.cos(),
+                            centre_y + radius * angle.sin(),
+                        )
+                    })
+                    .collect()
+            }
+            Geometry::Triangular => {
+                let left = column as f64 * cellule_size / 2.0;
+                let top = row as f64 * HEX_ROW_RATIO * cellule_size;
+                let bottom = top + HEX_ROW_RATIO * cellule_size;
+
+                if ((column + row) as i64).rem_euclid(2) == 0 {
+                    vec![
+                        (left + cellule_size / 2.0, top),
+                        (left + cellule_size, bottom),
+                        (left, bottom),
+                    ]
+                } else {
+                    vec![
+                        (left, top),
+                        (left + cellule_size, top),
+                        (left + cellule_size / 2.0, bottom),
+                    ]
+                }
+            }
+        }
+    }
+
+    /// The `(column, row)` of the cellule under a point on the canvas.
+    pub fn cellule_at(
+        &self,
+        x: f64,
+        y: f64,
+        width: usize,
+        height: usize,
+        cellule_size: f64,
+    ) -> Option<(usize, usize)> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+
+        let found = match self {
+            Geometry::Square => Some(((x / cellule_size) as usize, (y / cellule_size) as usize)),
+            // Hexagons are exactly the area closest to their centre.
+            Geometry::Hexagonal => {
+                let row = ((y / cellule_size - 0.5) / HEX_ROW_RATIO).round() as i64;
+                let column = (x / cellule_size) as i64;
+
+                let mut candidates = vec![];
+                for candidate_row in (row - 1)..=(row + 1) {
+                    for candidate_column in (column - 1)..=(column + 1) {
+                        if candidate_row >= 0 && candidate_column >= 0 {
+                            candidates.push((candidate_column as usize, candidate_row as usize));
+                        }
+                    }
+                }
+
+                candidates.into_iter().min_by(|a, b| {
+                    let distance = |&(column, row): &(usize, usize)| {
+                        let (centre_x, centre_y) = self.cellule_centre(column, row, cellule_size);
+                        (centre_x - x).powi(2) + (centre_y - y).powi(2)
+                    };
+                    distance(a).partial_cmp(&distance(b)).unwrap()
+                })
+            }
+            Geometry::Triangular => {
+                let row = (y / (HEX_ROW_RATIO * cellule_size)) as usize;
+                let column = (x / (cellule_size / 2.0)) as i64;
+
+                ((column - 1)..=column)
+                    .filter(|column| *column >= 0)
+                    .map(|column| (column as usize, row))
+                    .find(|&(column, row)| {
+                        contains(&self.cellule_outline(column, row, cellule_size), x, y)
+                    })
+            }
+        };
+
+        found.filter(|&(column, row)| column < width && row < height)
+    }
+}
+
+/// Alive flags for a board with `pattern` placed around its centre.
+pub fn pattern_cellules(pattern: &[(i64, i64)], width: usize, height: usize) -> Vec<bool> {
+    let mut cellules = vec![false; width * height];
+    let (centre_x, centre_y) = ((width / 2) as i64, (height / 2) as i64);
+
+    for (offset_x, offset_y) in pattern {
+        let (x, y) = (centre_x + offset_x, centre_y + offset_y);
+        if x >= 0 && y >= 0 && x < width as i64 && y < height as i64 {
+            cellules[(y as usize) * width + x as usize] = true;
+        }
+    }
+    cellules
+}
+
+fn contains(triangle: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let side =
+        |(ax, ay): (f64, f64), (bx, by): (f64, f64)| (bx - ax) * (y - ay) - (by - ay) * (x - ax);
+
+    let sides = [
+        side(triangle[0], triangle[1]),
+        side(triangle[1], triangle[2]),
+        side(triangle[2], triangle[0]),
+    ];
+
+    sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0)
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_neighbor_counts() {
+        assert_eq!(Geometry::Square.neighbors(5 * 10 + 5, 10, 10).len(), 8);
+        assert_eq!(Geometry::Hexagonal.neighbors(5 * 10 + 5, 10, 10).len(), 6);
+        assert_eq!(Geometry::Triangular.neighbors(5 * 10 + 5, 10, 10).len(), 12);
+
+        assert_eq!(Geometry::Square.neighbors(0, 10, 10).len(), 3);
+        assert_eq!(Geometry::Hexagonal.neighbors(0, 10, 10).len(), 2);
+    }
+
+    #[test]
+    fn test_neighbors_are_symmetric() {
+        for geometry in Geometry::all() {
+            for index in 0..(12 * 9) {
+                for neighbor in geometry.neighbors(index, 12, 9) {
+                    assert!(
+                        geometry.neighbors(neighbor, 12, 9).contains(&index),
+                        "{:?}: {} -> {}",
+                        geometry,
+                        index,
+                        neighbor
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hit_testing_finds_the_centre_cellule() {
+        for geometry in Geometry::all() {
+            for row in 0..9 {
+                for column in 0..12 {
+                    let (x, y) = geometry.cellule_centre(column, row, 20.0);
+                    assert_eq!(
+                        geometry.cellule_at(x, y, 12, 9, 20.0),
+                        Some((column, row)),
+                        "{:?}",
+                        geometry
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hit_testing_outside_the_board() {
+        for geometry in Geometry::all() {
+            let (canvas_width, canvas_height) = geometry.canvas_size(12, 9, 20.0);
+            assert_eq!(geometry.cellule_at(-1.0, 5.0, 12, 9, 20.0), None);
+            assert_eq!(
+                geometry.cellule_at(canvas_width + 30.0, canvas_height + 30.0, 12, 9, 20.0),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,422 @@
+use std::cmp::min;
+use std::collections::HashMap;
+
+use game_of_life_core::core::game::GameState;
+
+use super::Engine;
+
+type NodeId = usize;
+
+// Leaves are the only level 0 nodes and always sit at these indexes.
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+// Everything outside the board is wall: never alive, never counted as a neighbor.
+const WALL: NodeId = 2;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+struct Quad {
+    nw: NodeId,
+    ne: NodeId,
+    sw: NodeId,
+    se: NodeId,
+}
+
+struct Node {
+    level: u8,
+    quad: Quad,
+    population: u64,
+}
+
+/// Memoized quadtree engine (HashLife) for the bounded board.
+///
+/// The board is embedded in an infinite plane of wall cells, so identical
+/// regions share one node and their futures are computed once. That lets
+/// `step_by` jump any power of two generations in roughly the same time as a
+/// single step once the caches are warm.
+pub struct HashLife {
+    nodes: Vec<Node>,
+    interned: HashMap<Quad, NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    walls: Vec<NodeId>,
+    root: NodeId,
+    origin: (i64, i64),
+    width: usize,
+    height: usize,
+    generation: u64,
+}
+
+impl HashLife {
+    pub fn new(width: usize, height: usize, alive_cellules: &[bool]) -> HashLife {
+        let leaf = |population| Node {
+            level: 0,
+            quad: Quad {
+                nw: DEAD,
+                ne: DEAD,
+                sw: DEAD,
+                se: DEAD,
+            },
+            population,
+        };
+
+        let mut hashlife = HashLife {
+            nodes: vec![leaf(0), leaf(1), leaf(0)],
+            interned: HashMap::new(),
+            results: HashMap::new(),
+            walls: vec![WALL],
+            root: DEAD,
+            origin: (0, 0),
+            width,
+            height,
+            generation: 0,
+        };
+
+        let mut level = 2;
+        while (1 << level) < width.max(height) {
+            level += 1;
+        }
+        hashlife.root = hashlife.build(level, 0, 0, alive_cellules);
+
+        hashlife
+    }
+
+    pub fn from_game_state(game_state: &GameState) -> HashLife {
+        HashLife::new(
+            game_state.cellules_width,
+            game_state.cellules_height,
+            &Engine::alive_cellules(game_state),
+        )
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Advances exactly `2^power` generations.
+    pub fn advance_power_of_two(&mut self, power: u8) {
+        while self.nodes[self.root].level < power + 2 {
+            self.expand();
+        }
+        // One more ring of walls keeps the board inside the part of the tree
+        // whose future is fully determined.
+        self.expand();
+
+        let level = self.nodes[self.root].level;
+        let offset = 1i64 << (level - 2);
+        self.root = self.successor(self.root, power);
+        self.origin = (self.origin.0 + offset, self.origin.1 + offset);
+        self.generation += 1 << power;
+    }
+
+    fn build(&mut self, level: u8, x: i64, y: i64, alive_cellules: &[bool]) -> NodeId {
+        let size = 1i64 << level;
+        if x >= self.width as i64 || y >= self.height as i64 || x + size <= 0 || y + size <= 0 {
+            return self.wall(level);
+        }
+
+        if level == 0 {
+            let index = (y as usize) * self.width + (x as usize);
+            return if alive_cellules.get(index) == Some(&true) {
+                ALIVE
+            } else {
+                DEAD
+            };
+        }
+
+        let half = size / 2;
+        let nw = self.build(level - 1, x, y, alive_cellules);
+        let ne = self.build(level - 1, x + half, y, alive_cellules);
+        let sw = self.build(level - 1, x, y + half, alive_cellules);
+        let se = self.build(level - 1, x + half, y + half, alive_cellules);
+
+        self.join(nw, ne, sw, se)
+    }
+
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let quad = Quad { nw, ne, sw, se };
+        if let Some(&id) = self.interned.get(&quad) {
+            return id;
+        }
+
+        let node = Node {
+            level: self.nodes[nw].level + 1,
+            quad,
+            population: self.nodes[nw].population
+                + self.nodes[ne].population
+                + self.nodes[sw].population
+                + self.nodes[se].population,
+        };
+        let id = self.nodes.len();
+        self.nodes.push(node);
+        self.interned.insert(quad, id);
+        id
+    }
+
+    fn wall(&mut self, level: u8) -> NodeId {
+        while self.walls.len() <= level as usize {
+            let below = *self.walls.last().unwrap();
+            let wall = self.join(below, below, below, below);
+            self.walls.push(wall);
+        }
+        self.walls[level as usize]
+    }
+
+    fn quad(&self, id: NodeId) -> Quad {
+        self.nodes[id].quad
+    }
+
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let quad = self.quad(id);
+        let (nw, ne, sw, se) = (
+            self.quad(quad.nw),
+            self.quad(quad.ne),
+            self.quad(quad.sw),
+            self.quad(quad.se),
+        );
+        self.join(nw.se, ne.sw, sw.ne, se.nw)
+    }
+
+    /// Wraps the root in a ring of walls, doubling its size around the centre.
+    fn expand(&mut self) {
+        let level = self.nodes[self.root].level;
+        let wall = self.wall(level - 1);
+        let quad = self.quad(self.root);
+
+        let nw = self.join(wall, wall, wall, quad.nw);
+        let ne = self.join(wall, wall, quad.ne, wall);
+        let sw = self.join(wall, quad.sw, wall, wall);
+        let se = self.join(quad.se, wall, wall, wall);
+        self.root = self.join(nw, ne, sw, se);
+
+        let offset = 1i64 << (level - 1);
+        self.origin = (self.origin.0 - offset, self.origin.1 - offset);
+    }
+
+    /// The centre half of `id`, `2^power` generations later.
+    fn successor(&mut self, id: NodeId, power: u8) -> NodeId {
+        let level = self.nodes[id].level;
+        let power = min(power, level - 2);
+
+        if self.nodes[id].population == 0 {
+            return self.centre(id);
+        }
+        if let Some(&result) = self.results.get(&(id, power)) {
+            return result;
+        }
+
+        let result = if level == 2 {
+            self.step_4x4(id)
+        } else {
+            let quad = self.quad(id);
+            let (a, b, c, d) = (
+                self.quad(quad.nw),
+                self.quad(quad.ne),
+                self.quad(quad.sw),
+                self.quad(quad.se),
+            );
+
+            let n00 = quad.nw;
+            let n01 = self.join(a.ne, b.nw, a.se, b.sw);
+            let n02 = quad.ne;
+            let n10 = self.join(a.sw, a.se, c.nw, c.ne);
+            let n11 = self.join(a.se, b.sw, c.ne, d.nw);
+            let n12 = self.join(b.sw, b.se, d.nw, d.ne);
+            let n20 = quad.sw;
+            let n21 = self.join(c.ne, d.nw, c.se, d.sw);
+            let n22 = quad.se;
+
+            if power < level - 2 {
+                let c00 = self.successor(n00, power);
+                let c01 = self.successor(n01, power);
+                let c02 = self.successor(n02, power);
+                let c10 = self.successor(n10, power);
+                let c11 = self.successor(n11, power);
+                let c12 = self.successor(n12, power);
+                let c20 = self.successor(n20, power);
+                let c21 = self.successor(n21, power);
+                let c22 = self.successor(n22, power);
+
+                let nw = self.join(c00, c01, c10, c11);
+                let ne = self.join(c01, c02, c11, c12);
+                let sw = self.join(c10, c11, c20, c21);
+                let se = self.join(c11, c12, c21, c22);
+
+                let nw = self.centre(nw);
+                let ne = self.centre(ne);
+                let sw = self.centre(sw);
+                let se = self.centre(se);
+                self.join(nw, ne, sw, se)
+            } else {
+                let half_power = power - 1;
+                let c00 = self.successor(n00, half_power);
+                let c01 = self.successor(n01, half_power);
+                let c02 = self.successor(n02, half_power);
+                let c10 = self.successor(n10, half_power);
+                let c11 = self.successor(n11, half_power);
+                let c12 = self.successor(n12, half_power);
+                let c20 = self.successor(n20, half_power);
+                let c21 = self.successor(n21, half_power);
+                let c22 = self.successor(n22, half_power);
+
+                let nw = self.join(c00, c01, c10, c11);
+                let ne = self.join(c01, c02, c11, c12);
+                let sw = self.join(c10, c11, c20, c21);
+                let se = self.join(c11, c12, c21, c22);
+
+                let nw = self.successor(nw, half_power);
+                let ne = self.successor(ne, half_power);
+                let sw = self.successor(sw, half_power);
+                let se = self.successor(se, half_power);
+                self.join(nw, ne, sw, se)
+            }
+        };
+
+        self.results.insert((id, power), result);
+        result
+    }
+
+    /// Brute force single generation for the centre 2x2 of a 4x4 node.
+    fn step_4x4(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[DEAD; 4]; 4];
+        for (y, row) in cells.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = self.leaf_at(id, x as i64, y as i64);
+            }
+        }
+
+        let mut next = [DEAD; 4];
+        for (index, cell) in next.iter_mut().enumerate() {
+            let x = 1 + index % 2;
+            let y = 1 + index / 2;
+
+            if cells[y][x] == WALL {
+                *cell = WALL;
+                continue;
+            }
+
+            let mut neighbors = cells[(y - 1)..=(y + 1)]
+                .iter()
+                .flat_map(|row| row[(x - 1)..=(x + 1)].iter())
+                .filter(|&&neighbor| neighbor == ALIVE)
+                .count();
+            if cells[y][x] == ALIVE {
+                neighbors -= 1;
+            }
+
+            *cell = if neighbors == 3 || (neighbors == 2 && cells[y][x] == ALIVE) {
+                ALIVE
+            } else {
+                DEAD
+            };
+        }
+
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    /// The leaf at `(x, y)`, relative to the top left corner of `id`.
+    fn leaf_at(&self, mut id: NodeId, mut x: i64, mut y: i64) -> NodeId {
+        while self.nodes[id].level > 0 {
+            let half = 1i64 << (self.nodes[id].level - 1);
+            let quad = self.quad(id);
+            id = match (x < half, y < half) {
+                (true, true) => quad.nw,
+                (false, true) => quad.ne,
+                (true, false) => quad.sw,
+                (false, false) => quad.se,
+            };
+            if x >= half {
+                x -= half;
+            }
+            if y >= half {
+                y -= half;
+            }
+        }
+        id
+    }
+}
+
+impl Engine for HashLife {
+    fn step(&mut self) {
+        self.advance_power_of_two(0);
+    }
+
+    fn step_by(&mut self, steps: u64) {
+        for power in 0..64 {
+            if steps & (1 << power) != 0 {
+                self.advance_power_of_two(power);
+            }
+        }
+    }
+
+    fn alive_cellules(&self) -> Vec<bool> {
+        let mut alive_cellules = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                let leaf = self.leaf_at(self.root, x - self.origin.0, y - self.origin.1);
+                alive_cellules.push(leaf == ALIVE);
+            }
+        }
+        alive_cellules
+    }
+
+    fn population(&self) -> usize {
+        self.nodes[self.root].population as usize
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use game_of_life_core::core::seeds::seeds::{get_seeds, Seed};
+
+    fn game_state_for(seed: &Seed) -> GameState {
+        GameState {
+            active: false,
+            cellules: seed.cellules.clone(),
+            cellules_width: 50,
+            cellules_height: 40,
+            cellule_neighbors: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_matches_game_state_on_every_seed() {
+        for seed in get_seeds() {
+            let mut game_state = game_state_for(&seed);
+            let mut hashlife = HashLife::from_game_state(&game_state);
+
+            for step in 1..=2000 {
+                Engine::step(&mut game_state);
+                hashlife.step();
+
+                assert_eq!(
+                    Engine::alive_cellules(&game_state),
+                    hashlife.alive_cellules(),
+                    "{} diverged at step {}",
+                    seed.label,
+                    step
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_jump_matches_stepping() {
+        for seed in get_seeds() {
+            let mut game_state = game_state_for(&seed);
+            let mut hashlife = HashLife::from_game_state(&game_state);
+
+            game_state.step_by(3000);
+            hashlife.step_by(3000);
+
+            assert_eq!(hashlife.generation(), 3000);
+            assert_eq!(
+                Engine::alive_cellules(&game_state),
+                hashlife.alive_cellules(),
+                "{} diverged after jumping",
+                seed.label
+            );
+            assert_eq!(Engine::population(&game_state), hashlife.population());
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! The Cellule Life simulation engines, shared by the web client and the
+//! native tools that replay submitted runs.
+
+pub mod census;
+pub mod daily;
+pub mod generations;
+pub mod geometry;
+pub mod hashlife;
+pub mod ltl;
+pub mod rules;
+pub mod sparse;
+
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use game_of_life_core::core::game::{Cellule, GameState, LifeState};
+
+/// Runs stop once they pass this many steps, even if they never settle.
+pub const MAX_STEP_COUNT: i32 = 4000;
+
+/// The plane a run is played on. Scores are only compared within a topology.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Topology {
+    #[default]
+    Bounded,
+    Infinite,
+}
+
+/// Anything that can advance a board of cellules one generation at a time.
+///
+/// `GameState` is the reference implementation; the other engines in this
+/// module trade memory or generality for speed and must agree with it.
+pub trait Engine {
+    fn step(&mut self);
+
+    /// Advances `steps` generations. Engines that can skip ahead override this.
+    fn step_by(&mut self, steps: u64) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Alive flags for every cellule of the board, row by row.
+    fn alive_cellules(&self) -> Vec<bool>;
+
+    fn population(&self) -> usize {
+        self.alive_cellules().iter().filter(|alive| **alive).count()
+    }
+}
+
+/// Engines for rules with dying states, which the grid draws fading out.
+pub trait MultiStateEngine: Engine {
+    /// The state of every cellule: 0 dead, 1 alive, 2 and up dying.
+    fn states(&self) -> &[u8];
+
+    /// Remembers the current generation and reports whether it was seen before.
+    fn is_in_endless_loop(&mut self) -> bool;
+
+    /// Brings a cellule to life, or kills it if it is alive, for an edit made
+    /// mid-run. Generations from before the edit no longer count as seen.
+    fn toggle_cellule(&mut self, index: usize);
+
+    /// Alive neighbors of every cellule as a share of the neighborhood, for
+    /// neighborhoods too large to draw one halo per neighbor. Empty otherwise.
+    fn neighbor_densities(&self) -> Vec<f32> {
+        Vec::new()
+    }
+}
+
+impl Engine for GameState {
+    fn step(&mut self) {
+        GameState::step(self);
+    }
+
+    fn alive_cellules(&self) -> Vec<bool> {
+        self.cellules
+            .iter()
+            .map(|cellule| cellule.life_state == LifeState::Alive)
+            .collect()
+    }
+}
+
+/// Copies the result of another engine back onto `game_state`, toggling only
+/// the cellules that differ so the neighbor bookkeeping stays consistent.
+pub fn sync_game_state(game_state: &mut GameState, alive_cellules: &[bool]) {
+    let current = Engine::alive_cellules(game_state);
+
+    for (index, alive) in alive_cellules.iter().enumerate() {
+        if current.get(index) != Some(alive) {
+            game_state.toggle_cellule(index);
+        }
+    }
+}
+
+/// Turns alive flags into `Cellule`s for rendering, copying everything but
+/// the life state from `template`.
+pub fn to_cellules(template: &Cellule, alive_cellules: &[bool]) -> Vec<Cellule> {
+    alive_cellules
+        .iter()
+        .map(|alive| {
+            let mut cellule = template.clone();
+            cellule.life_state = if *alive {
+                LifeState::Alive
+            } else {
+                LifeState::Dead
+            };
+            cellule
+        })
+        .collect()
+}
+
+/// Neighbors of every alive cellule, clockwise from the top left, in the
+/// order `GameGrid` expects. Cellules past the edge are reported as dead.
+pub fn neighbor_map(
+    cellules: &[Cellule],
+    width: usize,
+    height: usize,
+) -> HashMap<usize, Vec<Cellule>> {
+    let offsets = [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+        (-1, 0),
+    ];
+
+    let mut neighbors = HashMap::new();
+    for (index, cellule) in cellules.iter().enumerate() {
+        if cellule.life_state != LifeState::Alive {
+            continue;
+        }
+
+        let x = (index % width) as i64;
+        let y = (index / width) as i64;
+        let cellule_neighbors = offsets
+            .iter()
+            .map(|(offset_x, offset_y)| {
+                let (neighbor_x, neighbor_y) = (x + offset_x, y + offset_y);
+                if neighbor_x < 0
+                    || neighbor_y < 0
+                    || neighbor_x >= width as i64
+                    || neighbor_y >= height as i64
+                {
+                    let mut dead = cellule.clone();
+                    dead.life_state = LifeState::Dead;
+                    dead
+                } else {
+                    cellules[(neighbor_y as usize) * width + neighbor_x as usize].clone()
+                }
+            })
+            .collect();
+        neighbors.insert(index, cellule_neighbors);
+    }
+    neighbors
+}
@@ -0,0 +1,355 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use game_of_life_core::core::game::GameState;
+
+use super::rules::{NeighborhoodShape, Rule};
+use super::{Engine, MultiStateEngine};
+
+const ALIVE: u8 = 1;
+
+/// Engine for Larger than Life rules on the bounded square board.
+///
+/// Neighborhoods reach up to ten cellules away, so counts come from summed
+/// area tables of the alive cellules instead of visiting every neighbor: one
+/// over the board answers a Moore square in four lookups, and one over the
+/// board turned by 45 degrees, where diamonds become squares, answers a von
+/// Neumann diamond in four more. Cellules past the edge are dead, the same as
+/// `GameState`.
+pub struct LargerThanLife {
+    rule: Rule,
+    width: usize,
+    height: usize,
+    states: Vec<u8>,
+    seen_states: HashSet<u64>,
+}
+
+impl LargerThanLife {
+    pub fn new(rule: Rule, width: usize, height: usize, alive_cellules: &[bool]) -> LargerThanLife {
+        LargerThanLife::from_states(
+            rule,
+            width,
+            height,
+            alive_cellules.iter().map(|alive| *alive as u8).collect(),
+        )
+    }
+
+    /// Picks up a run mid-way, dying cellules included.
+    pub fn from_states(rule: Rule, width: usize, height: usize, states: Vec<u8>) -> LargerThanLife {
+        LargerThanLife {
+            rule,
+            width,
+            height,
+            states,
+            seen_states: HashSet::new(),
+        }
+    }
+
+    pub fn from_game_state(rule: Rule, game_state: &GameState) -> LargerThanLife {
+        LargerThanLife::new(
+            rule,
+            game_state.cellules_width,
+            game_state.cellules_height,
+            &Engine::alive_cellules(game_state),
+        )
+    }
+
+    /// Alive cellules in the neighborhood of every cellule.
+    pub fn neighbor_counts(&self) -> Vec<usize> {
+        let counts = match self.rule.neighborhood.shape {
+            NeighborhoodShape::Moore => self.moore_counts(),
+            NeighborhoodShape::VonNeumann => self.von_neumann_counts(),
+        };
+
+        if self.rule.neighborhood.include_middle {
+            counts
+        } else {
+            counts
+                .iter()
+                .zip(self.states.iter())
+                .map(|(count, state)| count - (*state == ALIVE) as usize)
+                .collect()
+        }
+    }
+
+    fn moore_counts(&self) -> Vec<usize> {
+        let (width, height) = (self.width, self.height);
+        let table = SummedAreaTable::new(width, height, |x, y| self.states[y * width + x] == ALIVE);
+        let range = self.rule.neighborhood.range as i64;
+
+        (0..(width * height))
+            .map(|index| {
+                let (x, y) = ((index % width) as i64, (index / width) as i64);
+                table.sum(x - range, y - range, x + range, y + range)
+            })
+            .collect()
+    }
+
+    /// Turned by 45 degrees, (x, y) sits at (x + y, x - y + height - 1), and
+    /// the diamond of cellules at most `range` steps away from it is the
+    /// square `range` away in both turned directions. Turned positions no
+    /// cellule lands on count as dead.
+    fn von_neumann_counts(&self) -> Vec<usize> {
+        let (width, height) = (self.width, self.height);
+        let size = (width + height).saturating_sub(1);
+        let table = SummedAreaTable::new(size, size, |u, v| {
+            // u + v and u - v are twice x and twice y, shifted by height - 1.
+            let shift = height as i64 - 1;
+            let (twice_x, twice_y) = ((u + v) as i64 - shift, u as i64 - v as i64 + shift);
+            if twice_x < 0 || twice_y < 0 || twice_x % 2 != 0 {
+                return false;
+            }
+            let (x, y) = ((twice_x / 2) as usize, (twice_y / 2) as usize);
+            x < width && y < height && self.states[y * width + x] == ALIVE
+        });
+        let range = self.rule.neighborhood.range as i64;
+
+        (0..(width * height))
+            .map(|index| {
+                let (x, y) = ((index % width) as i64, (index / width) as i64);
+                let (u, v) = (x + y, x - y + height as i64 - 1);
+                table.sum(u - range, v - range, u + range, v + range)
+            })
+            .collect()
+    }
+}
+
+/// Alive cellules in any rectangle of a grid, in four lookups.
+struct SummedAreaTable {
+    width: usize,
+    height: usize,
+    /// table[(y + 1) * (width + 1) + x + 1] holds the alive cellules above
+    /// and left of (x, y), inclusive.
+    table: Vec<usize>,
+}
+
+impl SummedAreaTable {
+    fn new(
+        width: usize,
+        height: usize,
+        is_alive: impl Fn(usize, usize) -> bool,
+    ) -> SummedAreaTable {
+        let stride = width + 1;
+        let mut table = vec![0; stride * (height + 1)];
+        for y in 0..height {
+            let mut row_sum = 0;
+            for x in 0..width {
+                row_sum += is_alive(x, y) as usize;
+                table[(y + 1) * stride + x + 1] = table[y * stride + x + 1] + row_sum;
+            }
+        }
+
+        SummedAreaTable {
+            width,
+            height,
+            table,
+        }
+    }
+
+    /// The alive cellules from (left, top) to (right, bottom), inclusive.
+    /// The parts outside the grid count as dead.
+    fn sum(&self, left: i64, top: i64, right: i64, bottom: i64) -> usize {
+        let clamp = |value: i64, limit: usize| value.max(0).min(limit as i64) as usize;
+        let (left, top) = (clamp(left, self.width), clamp(top, self.height));
+        let (right, bottom) = (clamp(right + 1, self.width), clamp(bottom + 1, self.height));
+        if left >= right || top >= bottom {
+            return 0;
+        }
+
+        let stride = self.width + 1;
+        self.table[bottom * stride + right] + self.table[top * stride + left]
+            - self.table[top * stride + right]
+            - self.table[bottom * stride + left]
+    }
+}
+
+impl Engine for LargerThanLife {
+    fn step(&mut self) {
+        let counts = self.neighbor_counts();
+
+        self.states = self
+            .states
+            .iter()
+            .zip(counts)
+            .map(|(state, count)| self.rule.next_state(*state, count))
+            .collect();
+    }
+
+    fn alive_cellules(&self) -> Vec<bool> {
+        self.states.iter().map(|state| *state == ALIVE).collect()
+    }
+}
+
+impl MultiStateEngine for LargerThanLife {
+    fn states(&self) -> &[u8] {
+        &self.states
+    }
+
+    fn is_in_endless_loop(&mut self) -> bool {
+        let mut hasher = DefaultHasher::new();
+        self.states.hash(&mut hasher);
+
+        !self.seen_states.insert(hasher.finish())
+    }
+
+    fn toggle_cellule(&mut self, index: usize) {
+        self.states[index] = if self.states[index] == ALIVE {
+            0
+        } else {
+            ALIVE
+        };
+        self.seen_states.clear();
+    }
+
+    fn neighbor_densities(&self) -> Vec<f32> {
+        let size = self.rule.neighborhood.size().max(1) as f32;
+
+        self.neighbor_counts()
+            .into_iter()
+            .map(|count| count as f32 / size)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::generations::GenerationsLife;
+    use crate::geometry::Geometry;
+
+    fn pseudo_random_cellules(length: usize) -> Vec<bool> {
+        let mut state: u32 = 2463534242;
+        (0..length)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state % 4 == 1
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_range_one_matches_generations() {
+        let cellules = pseudo_random_cellules(30 * 20);
+        let mut ltl = LargerThanLife::new(
+            Rule::parse("R1,C0,M0,S2..3,B3..3,NM").unwrap(),
+            30,
+            20,
+            &cellules,
+        );
+        let mut generations =
+            GenerationsLife::new(Rule::conway(), Geometry::Square, 30, 20, &cellules);
+
+        for step in 1..=200 {
+            ltl.step();
+            generations.step();
+
+            assert_eq!(
+                ltl.alive_cellules(),
+                generations.alive_cellules(),
+                "diverged at step {}",
+                step
+            );
+        }
+    }
+
+    /// Counts every neighborhood by visiting each of its cellules.
+    fn brute_force_counts(ltl: &LargerThanLife, cellules: &[bool]) -> Vec<usize> {
+        let (width, height) = (ltl.width, ltl.height);
+        let neighborhood = ltl.rule.neighborhood;
+        let range = neighborhood.range as i64;
+
+        (0..(width * height))
+            .map(|index| {
+                let (x, y) = ((index % width) as i64, (index / width) as i64);
+                let mut count = 0;
+                for offset_y in -range..=range {
+                    for offset_x in -range..=range {
+                        let (neighbor_x, neighbor_y) = (x + offset_x, y + offset_y);
+                        let inside = neighbor_x >= 0
+                            && neighbor_y >= 0
+                            && neighbor_x < width as i64
+                            && neighbor_y < height as i64;
+                        let in_shape = match neighborhood.shape {
+                            NeighborhoodShape::Moore => true,
+                            NeighborhoodShape::VonNeumann => {
+                                offset_x.abs() + offset_y.abs() <= range
+                            }
+                        };
+                        let is_middle = offset_x == 0 && offset_y == 0;
+
+                        if inside
+                            && in_shape
+                            && (neighborhood.include_middle || !is_middle)
+                            && cellules[(neighbor_y as usize) * width + neighbor_x as usize]
+                        {
+                            count += 1;
+                        }
+                    }
+                }
+                count
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_neighbor_counts_match_brute_force() {
+        let (width, height) = (17, 13);
+        let cellules = pseudo_random_cellules(width * height);
+
+        for rule in &["R3,C0,M1,S1..2,B1..2,NM", "R4,C0,M0,S1..2,B1..2,NN"] {
+            let ltl = LargerThanLife::new(Rule::parse(rule).unwrap(), width, height, &cellules);
+
+            assert_eq!(
+                ltl.neighbor_counts(),
+                brute_force_counts(&ltl, &cellules),
+                "{}",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn test_large_ranges_match_brute_force() {
+        for (width, height) in &[(64, 41), (23, 52), (1, 30)] {
+            let cellules = pseudo_random_cellules(width * height);
+
+            for rule in &[
+                "R10,C0,M1,S1..2,B1..2,NN",
+                "R10,C0,M0,S1..2,B1..2,NN",
+                "R10,C0,M0,S1..2,B1..2,NM",
+            ] {
+                let ltl =
+                    LargerThanLife::new(Rule::parse(rule).unwrap(), *width, *height, &cellules);
+
+                assert_eq!(
+                    ltl.neighbor_counts(),
+                    brute_force_counts(&ltl, &cellules),
+                    "{} on {}x{}",
+                    rule,
+                    width,
+                    height
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_densities_stay_in_range() {
+        let ltl = LargerThanLife::new(
+            Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap(),
+            5,
+            5,
+            &[true; 25],
+        );
+
+        let densities = ltl.neighbor_densities();
+        assert_eq!(densities.len(), 25);
+        assert!(densities
+            .iter()
+            .all(|density| *density > 0.0 && *density <= 1.0));
+    }
+}
@@ -0,0 +1,374 @@
+use anyhow::{anyhow, Error};
+use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+const DEAD: u8 = 0;
+const ALIVE: u8 = 1;
+// Cell states are serialized as one base 36 digit each.
+const MAX_STATES: u8 = 36;
+const MAX_RANGE: usize = 10;
+
+/// A Generations or Larger than Life rule, such as `B2/S345/C4` or
+/// `R5,C0,M1,S34..58,B34..45,NM`.
+///
+/// State 0 is dead and state 1 is alive. With more than two states a cellule
+/// that fails to survive counts up through the dying states 2.. before it is
+/// dead again, and only alive cellules count as neighbors. Plain Life rules
+/// like `B3/S23` are the two state case on the range 1 Moore neighborhood.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rule {
+    pub birth: Vec<bool>,
+    pub survival: Vec<bool>,
+    pub states: u8,
+    pub neighborhood: Neighborhood,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NeighborhoodShape {
+    Moore,
+    VonNeumann,
+}
+
+/// Which cellules are counted: everything within `range` steps, either as a
+/// square (Moore) or a diamond (von Neumann).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Neighborhood {
+    pub shape: NeighborhoodShape,
+    pub range: usize,
+    pub include_middle: bool,
+}
+
+impl Neighborhood {
+    pub fn moore() -> Neighborhood {
+        Neighborhood {
+            shape: NeighborhoodShape::Moore,
+            range: 1,
+            include_middle: false,
+        }
+    }
+
+    /// The most alive cellules the neighborhood can count.
+    pub fn size(&self) -> usize {
+        let range = self.range;
+        let cellules = match self.shape {
+            NeighborhoodShape::Moore => (2 * range + 1) * (2 * range + 1),
+            NeighborhoodShape::VonNeumann => 2 * range * (range + 1) + 1,
+        };
+        if self.include_middle {
+            cellules
+        } else {
+            cellules - 1
+        }
+    }
+}
+
+impl Rule {
+    pub fn conway() -> Rule {
+        Rule::parse("B3/S23").unwrap()
+    }
+
+    pub fn parse(rule: &str) -> Result<Rule, Error> {
+        let rule = rule.trim().to_uppercase();
+        if rule.starts_with('R') && rule.contains(',') {
+            Rule::parse_larger_than_life(&rule)
+        } else {
+            Rule::parse_generations(&rule)
+        }
+    }
+
+    fn parse_generations(rule: &str) -> Result<Rule, Error> {
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = 2;
+
+        for part in rule.split('/') {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some('B') => birth = Some(parse_counts(chars.as_str())?),
+                Some('S') => survival = Some(parse_counts(chars.as_str())?),
+                Some('C') | Some('G') => states = parse_number(chars.as_str(), rule)?,
+                _ => return Err(anyhow!("unexpected {:?} in rule {:?}", part, rule)),
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => {
+                Rule::new(birth, survival, states, Neighborhood::moore())
+            }
+            _ => Err(anyhow!("rule {:?} needs both B and S parts", rule)),
+        }
+    }
+
+    fn parse_larger_than_life(rule: &str) -> Result<Rule, Error> {
+        let mut range = None;
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = 2;
+        let mut include_middle = false;
+        let mut shape = NeighborhoodShape::Moore;
+
+        for part in rule.split(',') {
+            let mut chars = part.chars();
+            let value = chars.next();
+            let value = (value, chars.as_str());
+            match value {
+                (Some('R'), value) => range = Some(parse_number::<usize>(value, rule)?),
+                // LtL writes two states as C0.
+                (Some('C'), value) => states = parse_number::<u8>(value, rule)?.max(2),
+                (Some('M'), "0") => include_middle = false,
+                (Some('M'), "1") => include_middle = true,
+                (Some('S'), value) => survival = Some(parse_count_range(value, rule)?),
+                (Some('B'), value) => birth = Some(parse_count_range(value, rule)?),
+                (Some('N'), "M") => shape = NeighborhoodShape::Moore,
+                (Some('N'), "N") => shape = NeighborhoodShape::VonNeumann,
+                _ => return Err(anyhow!("unexpected {:?} in rule {:?}", part, rule)),
+            }
+        }
+
+        let range = range.ok_or_else(|| anyhow!("rule {:?} needs a range", rule))?;
+        if !(1..=MAX_RANGE).contains(&range) {
+            return Err(anyhow!("ranges go from 1 to {}", MAX_RANGE));
+        }
+
+        let neighborhood = Neighborhood {
+            shape,
+            range,
+            include_middle,
+        };
+        let enabled = |counts: Option<(usize, usize)>, part: &str| -> Result<Vec<bool>, Error> {
+            let (low, high) = counts.ok_or_else(|| anyhow!("rule {:?} needs {}", rule, part))?;
+            Ok((0..=neighborhood.size())
+                .map(|count| low <= count && count <= high)
+                .collect())
+        };
+
+        Rule::new(
+            enabled(birth, "B")?,
+            enabled(survival, "S")?,
+            states,
+            neighborhood,
+        )
+    }
+
+    fn new(
+        birth: Vec<bool>,
+        survival: Vec<bool>,
+        states: u8,
+        neighborhood: Neighborhood,
+    ) -> Result<Rule, Error> {
+        if !(2..=MAX_STATES).contains(&states) {
+            return Err(anyhow!("rules need between 2 and {} states", MAX_STATES));
+        }
+
+        Ok(Rule {
+            birth,
+            survival,
+            states,
+            neighborhood,
+        })
+    }
+
+    pub fn is_conway(&self) -> bool {
+        *self == Rule::conway()
+    }
+
+    /// Anything but the range 1 Moore neighborhood needs the Larger than
+    /// Life engine.
+    pub fn is_larger_than_life(&self) -> bool {
+        self.neighborhood != Neighborhood::moore()
+    }
+
+    /// Geometries with more than eight neighbors can reach counts the rule
+    /// has no digit for; those never give birth or survive.
+    pub fn next_state(&self, state: u8, alive_neighbors: usize) -> u8 {
+        match state {
+            DEAD if self.birth.get(alive_neighbors) == Some(&true) => ALIVE,
+            DEAD => DEAD,
+            ALIVE if self.survival.get(alive_neighbors) == Some(&true) => ALIVE,
+            dying if dying + 1 < self.states => dying + 1,
+            _ => DEAD,
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_larger_than_life() {
+            let bounds = |enabled: &[bool]| {
+                let low = enabled.iter().position(|enabled| *enabled).unwrap_or(0);
+                let high = enabled.iter().rposition(|enabled| *enabled).unwrap_or(0);
+                format!("{}..{}", low, high)
+            };
+
+            return write!(
+                formatter,
+                "R{},C{},M{},S{},B{},N{}",
+                self.neighborhood.range,
+                if self.states > 2 { self.states } else { 0 },
+                self.neighborhood.include_middle as u8,
+                bounds(&self.survival),
+                bounds(&self.birth),
+                match self.neighborhood.shape {
+                    NeighborhoodShape::Moore => "M",
+                    NeighborhoodShape::VonNeumann => "N",
+                }
+            );
+        }
+
+        let counts = |enabled: &[bool]| {
+            (0..enabled.len())
+                .filter(|count| enabled[*count])
+                .map(|count| count.to_string())
+                .collect::<String>()
+        };
+
+        write!(
+            formatter,
+            "B{}/S{}",
+            counts(&self.birth),
+            counts(&self.survival)
+        )?;
+        if self.states > 2 {
+            write!(formatter, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = Error;
+
+    fn try_from(rule: String) -> Result<Self, Self::Error> {
+        Rule::parse(&rule)
+    }
+}
+
+impl From<Rule> for String {
+    fn from(rule: Rule) -> Self {
+        rule.to_string()
+    }
+}
+
+fn parse_counts(counts: &str) -> Result<Vec<bool>, Error> {
+    let mut enabled = vec![false; 9];
+    for count in counts.chars() {
+        match count.to_digit(10) {
+            Some(count) if count < 9 => enabled[count as usize] = true,
+            _ => return Err(anyhow!("invalid neighbor count {:?}", count)),
+        }
+    }
+    Ok(enabled)
+}
+
+fn parse_count_range(counts: &str, rule: &str) -> Result<(usize, usize), Error> {
+    let mut bounds = counts.splitn(2, ['.', '-']);
+    let low = parse_number(bounds.next().unwrap_or(""), rule)?;
+    let high = parse_number(bounds.next().unwrap_or("").trim_start_matches('.'), rule)?;
+
+    if low > high {
+        return Err(anyhow!("empty neighbor range {:?} in {:?}", counts, rule));
+    }
+    Ok((low, high))
+}
+
+fn parse_number<T: std::str::FromStr>(number: &str, rule: &str) -> Result<T, Error> {
+    number
+        .parse::<T>()
+        .map_err(|_| anyhow!("invalid number {:?} in rule {:?}", number, rule))
+}
+
+/// The rules offered in the header, with their display names.
+pub fn rule_presets() -> Vec<(&'static str, Rule)> {
+    vec![
+        ("Life", Rule::conway()),
+        ("Brian's Brain", Rule::parse("B2/S/C3").unwrap()),
+        ("Star Wars", Rule::parse("B2/S345/C4").unwrap()),
+        ("Frogs", Rule::parse("B34/S12/C3").unwrap()),
+        ("Bloomerang", Rule::parse("B34678/S234/C24").unwrap()),
+        ("Bosco", Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap()),
+        (
+            "Majority",
+            Rule::parse("R4,C0,M1,S41..81,B41..81,NM").unwrap(),
+        ),
+        ("Diamonds", Rule::parse("R3,C0,M0,S5..9,B7..8,NN").unwrap()),
+    ]
+}
+
+/// One base 36 digit per cellule, so Life boards keep their old `"0"`/`"1"` form.
+pub fn serialize_states(states: &[u8]) -> String {
+    states
+        .iter()
+        .map(|state| std::char::from_digit(*state as u32, MAX_STATES as u32).unwrap_or('0'))
+        .collect()
+}
+
+pub fn deserialize_states(serialized: &str) -> Option<Vec<u8>> {
+    serialized
+        .chars()
+        .map(|state| state.to_digit(MAX_STATES as u32).map(|state| state as u8))
+        .collect()
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let star_wars = Rule::parse("b2/s345/c4").unwrap();
+        assert!(star_wars.birth[2] && !star_wars.birth[3]);
+        assert!(star_wars.survival[3] && star_wars.survival[5] && !star_wars.survival[2]);
+        assert_eq!(star_wars.states, 4);
+        assert_eq!(star_wars.to_string(), "B2/S345/C4");
+
+        assert_eq!(Rule::parse("B3/S23").unwrap().to_string(), "B3/S23");
+        assert_eq!(Rule::parse("B2/S/C3").unwrap().to_string(), "B2/S/C3");
+
+        let bosco = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+        assert!(bosco.is_larger_than_life());
+        assert_eq!(bosco.neighborhood.size(), 121);
+        assert!(bosco.birth[34] && bosco.birth[45] && !bosco.birth[46]);
+        assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!(
+            Rule::parse("R2,C3,M0,S2-3,B3-3,NN").unwrap().to_string(),
+            "R2,C3,M0,S2..3,B3..3,NN"
+        );
+        assert!(!Rule::conway().is_larger_than_life());
+
+        assert!(Rule::parse("B3").is_err());
+        assert!(Rule::parse("R5,C0,M1,S58..34,B34..45,NM").is_err());
+        assert!(Rule::parse("R50,C0,M1,S34..58,B34..45,NM").is_err());
+        assert!(Rule::parse("B9/S23").is_err());
+        assert!(Rule::parse("B3/S23/C40").is_err());
+    }
+
+    #[test]
+    fn test_dying_states() {
+        let rule = Rule::parse("B2/S345/C4").unwrap();
+
+        assert_eq!(rule.next_state(DEAD, 2), ALIVE);
+        assert_eq!(rule.next_state(ALIVE, 4), ALIVE);
+        assert_eq!(rule.next_state(ALIVE, 1), 2);
+        assert_eq!(rule.next_state(2, 2), 3);
+        assert_eq!(rule.next_state(3, 2), DEAD);
+    }
+
+    #[test]
+    fn test_states_round_trip() {
+        let states = vec![0, 1, 2, 3, 23, 0, 1];
+        let serialized = serialize_states(&states);
+
+        assert_eq!(serialized, "0123n01");
+        assert_eq!(deserialize_states(&serialized), Some(states));
+        assert_eq!(deserialize_states("01!"), None);
+    }
+}
@@ -0,0 +1,289 @@
+use std::cmp::{max, min};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use game_of_life_core::core::game::GameState;
+
+use super::Engine;
+
+// The viewport never grows past this many times the board, so a pair of
+// gliders flying apart can't produce an enormous canvas.
+const MAX_VIEWPORT_SCALE: usize = 5;
+const VIEWPORT_MARGIN: usize = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: i64,
+    pub min_y: i64,
+    pub max_x: i64,
+    pub max_y: i64,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> usize {
+        (self.max_x - self.min_x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max_y - self.min_y + 1) as usize
+    }
+}
+
+/// Engine for an unbounded plane that only stores the alive cellules.
+///
+/// The board the run started from is kept around as a window so the result
+/// can still be compared with (and copied back onto) a `GameState`.
+pub struct SparseLife {
+    alive: HashSet<(i64, i64)>,
+    width: usize,
+    height: usize,
+    seen_states: HashSet<u64>,
+    last_changes: (usize, usize),
+}
+
+impl SparseLife {
+    pub fn new(width: usize, height: usize, alive_cellules: &[bool]) -> SparseLife {
+        let alive = alive_cellules
+            .iter()
+            .enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(index, _)| ((index % width) as i64, (index / width) as i64))
+            .collect();
+
+        SparseLife {
+            alive,
+            width,
+            height,
+            seen_states: HashSet::new(),
+            last_changes: (0, 0),
+        }
+    }
+
+    pub fn from_game_state(game_state: &GameState) -> SparseLife {
+        SparseLife::new(
+            game_state.cellules_width,
+            game_state.cellules_height,
+            &Engine::alive_cellules(game_state),
+        )
+    }
+
+    /// Births and deaths in the last generation, anywhere on the plane.
+    pub fn last_changes(&self) -> (usize, usize) {
+        self.last_changes
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut cells = self.alive.iter();
+        let &(x, y) = cells.next()?;
+
+        Some(cells.fold(
+            BoundingBox {
+                min_x: x,
+                min_y: y,
+                max_x: x,
+                max_y: y,
+            },
+            |bounds, &(x, y)| BoundingBox {
+                min_x: min(bounds.min_x, x),
+                min_y: min(bounds.min_y, y),
+                max_x: max(bounds.max_x, x),
+                max_y: max(bounds.max_y, y),
+            },
+        ))
+    }
+
+    /// The region worth drawing: the bounding box plus a margin, grown to
+    /// at least the original board and to the board's aspect ratio.
+    pub fn viewport(&self) -> BoundingBox {
+        let bounds = self.bounding_box().unwrap_or(BoundingBox {
+            min_x: 0,
+            min_y: 0,
+            max_x: self.width as i64 - 1,
+            max_y: self.height as i64 - 1,
+        });
+
+        let width = max(bounds.width() + 2 * VIEWPORT_MARGIN, self.width);
+        let height = max(bounds.height() + 2 * VIEWPORT_MARGIN, self.height);
+
+        let width = max(width, height * self.width / self.height);
+        let height = max(height, width * self.height / self.width);
+
+        let width = min(width, self.width * MAX_VIEWPORT_SCALE) as i64;
+        let height = min(height, self.height * MAX_VIEWPORT_SCALE) as i64;
+
+        let centre_x = (bounds.min_x + bounds.max_x) / 2;
+        let centre_y = (bounds.min_y + bounds.max_y) / 2;
+
+        BoundingBox {
+            min_x: centre_x - width / 2,
+            min_y: centre_y - height / 2,
+            max_x: centre_x - width / 2 + width - 1,
+            max_y: centre_y - height / 2 + height - 1,
+        }
+    }
+
+    /// Alive flags for every cell inside `bounds`, row by row.
+    pub fn cellules_in(&self, bounds: &BoundingBox) -> Vec<bool> {
+        let mut cellules = Vec::with_capacity(bounds.width() * bounds.height());
+        for y in bounds.min_y..=bounds.max_y {
+            for x in bounds.min_x..=bounds.max_x {
+                cellules.push(self.alive.contains(&(x, y)));
+            }
+        }
+        cellules
+    }
+
+    /// The alive cellules on the starting board window, which is what a run
+    /// submits and is scored on. Cellules that left it still count towards
+    /// `population`.
+    pub fn population_on_board(&self) -> usize {
+        self.alive
+            .iter()
+            .filter(|&&(x, y)| x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64)
+            .count()
+    }
+
+    /// Remembers the current generation and reports whether it was seen before.
+    pub fn is_in_endless_loop(&mut self) -> bool {
+        let mut cells = self.alive.iter().cloned().collect::<Vec<(i64, i64)>>();
+        cells.sort();
+
+        let mut hasher = DefaultHasher::new();
+        cells.hash(&mut hasher);
+
+        !self.seen_states.insert(hasher.finish())
+    }
+
+    /// Flips a cellule of the starting board window, for an edit made mid-run.
+    /// Generations from before the edit no longer count as seen.
+    pub fn toggle_cellule(&mut self, index: usize) {
+        let cell = ((index % self.width) as i64, (index / self.width) as i64);
+        if !self.alive.remove(&cell) {
+            self.alive.insert(cell);
+        }
+        self.seen_states.clear();
+    }
+}
+
+impl Engine for SparseLife {
+    fn step(&mut self) {
+        let mut neighbor_counts: HashMap<(i64, i64), u8> = HashMap::new();
+        for &(x, y) in self.alive.iter() {
+            for neighbor_y in (y - 1)..=(y + 1) {
+                for neighbor_x in (x - 1)..=(x + 1) {
+                    if (neighbor_x, neighbor_y) != (x, y) {
+                        *neighbor_counts.entry((neighbor_x, neighbor_y)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let alive = &self.alive;
+        let next_alive: HashSet<(i64, i64)> = neighbor_counts
+            .into_iter()
+            .filter(|(cell, count)| *count == 3 || (*count == 2 && alive.contains(cell)))
+            .map(|(cell, _)| cell)
+            .collect();
+
+        let births = next_alive.difference(alive).count();
+        let deaths = alive.len() + births - next_alive.len();
+        self.last_changes = (births, deaths);
+        self.alive = next_alive;
+    }
+
+    fn alive_cellules(&self) -> Vec<bool> {
+        self.cellules_in(&BoundingBox {
+            min_x: 0,
+            min_y: 0,
+            max_x: self.width as i64 - 1,
+            max_y: self.height as i64 - 1,
+        })
+    }
+
+    fn population(&self) -> usize {
+        self.alive.len()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    fn glider() -> SparseLife {
+        let mut cellules = vec![false; 50 * 40];
+        for &(x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter() {
+            cellules[y * 50 + x] = true;
+        }
+        SparseLife::new(50, 40, &cellules)
+    }
+
+    #[test]
+    fn test_glider_leaves_the_board() {
+        let mut sparse = glider();
+        assert_eq!(sparse.population_on_board(), 5);
+        sparse.step_by(400);
+
+        // Still alive on the plane, but gone from the board.
+        assert_eq!(sparse.population(), 5);
+        assert_eq!(sparse.population_on_board(), 0);
+        assert!(sparse.alive_cellules().iter().all(|alive| !alive));
+
+        let bounds = sparse.bounding_box().unwrap();
+        assert_eq!((bounds.min_x, bounds.min_y), (100, 100));
+        assert_eq!((bounds.width(), bounds.height()), (3, 3));
+    }
+
+    #[test]
+    fn test_viewport_follows_pattern() {
+        let mut sparse = glider();
+        sparse.step_by(400);
+
+        let viewport = sparse.viewport();
+        assert_eq!((viewport.width(), viewport.height()), (50, 40));
+        assert!(viewport.min_x <= 100 && viewport.max_x >= 102);
+        assert!(viewport.min_y <= 100 && viewport.max_y >= 102);
+        assert_eq!(
+            sparse
+                .cellules_in(&viewport)
+                .iter()
+                .filter(|alive| **alive)
+                .count(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_detects_endless_loop() {
+        let mut cellules = vec![false; 50 * 40];
+        cellules[50 + 1] = true;
+        cellules[50 + 2] = true;
+        cellules[50 + 3] = true;
+        let mut sparse = SparseLife::new(50, 40, &cellules);
+
+        assert!(!sparse.is_in_endless_loop());
+        sparse.step();
+        assert!(!sparse.is_in_endless_loop());
+        sparse.step();
+        assert!(sparse.is_in_endless_loop());
+
+        sparse.toggle_cellule(50 * 39 + 49);
+        assert_eq!(sparse.population(), 4);
+        assert!(!sparse.is_in_endless_loop());
+    }
+
+    #[test]
+    fn test_counts_births_and_deaths() {
+        let mut sparse = glider();
+        assert_eq!(sparse.last_changes(), (0, 0));
+
+        sparse.step();
+        assert_eq!(sparse.last_changes(), (2, 2));
+
+        sparse.toggle_cellule(0);
+        sparse.step();
+        let (births, deaths) = sparse.last_changes();
+        assert_eq!(births + 6, deaths + sparse.population());
+    }
+}
@@ -0,0 +1,180 @@
+//! A rolling hash over every generation of a run.
+//!
+//! Each generation's hash covers the previous hash and the board, so the last
+//! hash commits to the whole run. Every `CHECKPOINT_INTERVAL` generations, and
+//! at the last one, the chain records a checkpoint with the hash and the board.
+//! A verifier can start from any checkpoint, replay to the next one and
+//! compare, instead of replaying the whole run.
+//!
+//! The hash is 64 bit FNV-1a, which gives the same result in the browser and
+//! natively.
+
+use serde_derive::{Deserialize, Serialize};
+
+use cellule_life_engine::rules::serialize_states;
+
+/// Generations between two checkpoints.
+pub const CHECKPOINT_INTERVAL: i32 = 250;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The chain as it stood after `step_index` generations.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub step_index: i32,
+    /// The chain hash, as 16 hex digits.
+    pub hash: String,
+    /// The board, serialized like `SerializedGameState::cellules`.
+    pub cellules: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct HashChain {
+    step_index: i32,
+    hash: u64,
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl HashChain {
+    /// Starts a chain from the board the run starts on, edits included.
+    pub fn new(states: &[u8]) -> HashChain {
+        HashChain {
+            step_index: 0,
+            hash: fnv(FNV_OFFSET_BASIS, states),
+            checkpoints: vec![],
+        }
+    }
+
+    /// Continues a chain from one of its checkpoints.
+    pub fn resume(checkpoint: &Checkpoint) -> Option<HashChain> {
+        Some(HashChain {
+            step_index: checkpoint.step_index,
+            hash: u64::from_str_radix(&checkpoint.hash, 16).ok()?,
+            checkpoints: vec![],
+        })
+    }
+
+    /// Adds the next generation.
+    pub fn push(&mut self, states: &[u8]) {
+        self.step_index += 1;
+        self.hash = fnv(self.hash, states);
+
+        if self.step_index % CHECKPOINT_INTERVAL == 0 {
+            self.record(states);
+        }
+    }
+
+    /// Records the last generation of the run, unless it already has a checkpoint.
+    pub fn finish(&mut self, states: &[u8]) {
+        let is_recorded = self
+            .checkpoints
+            .last()
+            .is_some_and(|checkpoint| checkpoint.step_index == self.step_index);
+
+        if !is_recorded {
+            self.record(states);
+        }
+    }
+
+    pub fn step_index(&self) -> i32 {
+        self.step_index
+    }
+
+    pub fn hash(&self) -> String {
+        format!("{:016x}", self.hash)
+    }
+
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    fn record(&mut self, states: &[u8]) {
+        self.checkpoints.push(Checkpoint {
+            step_index: self.step_index,
+            hash: self.hash(),
+            cellules: serialize_states(states),
+        });
+    }
+}
+
+fn fnv(previous: u64, states: &[u8]) -> u64 {
+    previous
+        .to_le_bytes()
+        .iter()
+        .chain(states)
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    fn run(boards: &[Vec<u8>]) -> HashChain {
+        let mut chain = HashChain::new(&boards[0]);
+        for states in boards[1..].iter() {
+            chain.push(states);
+        }
+        chain.finish(boards.last().unwrap());
+        chain
+    }
+
+    fn boards(count: usize) -> Vec<Vec<u8>> {
+        (0..count)
+            .map(|generation| vec![(generation % 3) as u8, (generation % 7 == 0) as u8, 0, 1])
+            .collect()
+    }
+
+    #[test]
+    fn test_checkpoints_every_interval_and_at_the_end() {
+        let chain = run(&boards(601));
+        let steps: Vec<i32> = chain
+            .checkpoints()
+            .iter()
+            .map(|checkpoint| checkpoint.step_index)
+            .collect();
+
+        assert_eq!(steps, vec![250, 500, 600]);
+        assert_eq!(chain.checkpoints()[2].hash, chain.hash());
+        assert_eq!(chain.checkpoints()[2].cellules, "0001");
+
+        // A run ending on an interval is not recorded twice.
+        assert_eq!(run(&boards(501)).checkpoints().len(), 2);
+    }
+
+    #[test]
+    fn test_any_changed_generation_changes_the_hash() {
+        let honest = run(&boards(300));
+
+        let mut tampered = boards(300);
+        tampered[120][3] = 0;
+        let tampered = run(&tampered);
+
+        assert_eq!(honest.checkpoints()[0].step_index, 250);
+        assert_ne!(honest.checkpoints()[0].hash, tampered.checkpoints()[0].hash);
+        assert_ne!(honest.hash(), tampered.hash());
+    }
+
+    #[test]
+    fn test_resume_continues_the_chain() {
+        let boards = boards(400);
+        let honest = run(&boards);
+
+        let mut resumed = HashChain::resume(&honest.checkpoints()[0]).unwrap();
+        for states in boards[251..].iter() {
+            resumed.push(states);
+        }
+
+        assert_eq!(resumed.step_index(), 399);
+        assert_eq!(resumed.hash(), honest.hash());
+        assert!(HashChain::resume(&Checkpoint {
+            step_index: 250,
+            hash: "not hex".to_owned(),
+            cellules: String::new(),
+        })
+        .is_none());
+    }
+}
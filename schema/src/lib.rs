@@ -0,0 +1,342 @@
+//! The scores API wire format.
+//!
+//! The client, the mock server and the verifier all read and write these
+//! types, so a submission means the same thing everywhere. Every payload
+//! carries a `schema_version`; payloads from before the field existed are
+//! version 1 and are upgraded on decode. How scores rank against each other
+//! lives in `ranking`, so every side agrees on that too.
+//!
+//! Versions:
+//! 1. No `schema_version`. `topology`, `rule` and `geometry` may be missing
+//!    and default to the bounded square Life board.
+//! 2. Adds `schema_version`.
+//! 3. Adds hash chain `checkpoints`. Older payloads have none.
+//! 4. Adds `edit_budget`. Older payloads had unlimited edits.
+
+pub mod chain;
+pub mod ranking;
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use cellule_life_engine::geometry::Geometry;
+use cellule_life_engine::rules::Rule;
+use cellule_life_engine::Topology;
+
+pub use crate::chain::{Checkpoint, HashChain, CHECKPOINT_INTERVAL};
+
+pub const SCHEMA_VERSION: u32 = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GridModification {
+    pub step_index: i32,
+    pub grid_index: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SerializedGameState {
+    /// One base 36 digit per cellule state, row by row.
+    pub cellules: String,
+    pub active: bool,
+    pub cellules_width: usize,
+    pub cellules_height: usize,
+}
+
+/// A finished run, as posted to `submit-result`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SendResultPayload {
+    #[serde(default = "first_version")]
+    pub schema_version: u32,
+    pub game_state: SerializedGameState,
+    pub step_count: i32,
+    pub active_count: i32,
+    pub modifications: Vec<GridModification>,
+    pub seed_label: String,
+    pub user_name: String,
+    #[serde(default)]
+    pub topology: Topology,
+    #[serde(default)]
+    pub rule: Rule,
+    #[serde(default)]
+    pub geometry: Geometry,
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+    /// How many edits the run was allowed, if it was limited.
+    #[serde(default)]
+    pub edit_budget: Option<usize>,
+}
+
+/// A stored score: the submitted run plus the id the store gave it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetScoresResponseDataItem {
+    #[serde(flatten)]
+    pub payload: SendResultPayload,
+    #[serde(rename = "_id")]
+    pub id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetScoresResponseData {
+    pub scores: Vec<GetScoresResponseDataItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResultResponseData {
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaError {
+    Json(String),
+    /// Written by a newer client than this build understands.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::Json(reason) => write!(formatter, "invalid payload: {}", reason),
+            SchemaError::UnsupportedVersion(version) => write!(
+                formatter,
+                "payload schema version {} is newer than {}",
+                version, SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SchemaError {
+    fn from(error: serde_json::Error) -> Self {
+        SchemaError::Json(error.to_string())
+    }
+}
+
+impl SendResultPayload {
+    /// Decodes a payload of any supported version, upgraded to the current one.
+    pub fn decode(json: &str) -> Result<SendResultPayload, SchemaError> {
+        serde_json::from_str::<SendResultPayload>(json)?.upgrade()
+    }
+
+    pub fn from_value(value: Value) -> Result<SendResultPayload, SchemaError> {
+        serde_json::from_value::<SendResultPayload>(value)?.upgrade()
+    }
+
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Brings an older payload up to `SCHEMA_VERSION`.
+    pub fn upgrade(mut self) -> Result<SendResultPayload, SchemaError> {
+        match self.schema_version {
+            // Version 1 only lacked the version; serde filled in its defaults.
+            1 => {
+                self.schema_version = 2;
+                self.upgrade()
+            }
+            // Version 2 runs were never hashed, so they keep no checkpoints.
+            2 => {
+                self.schema_version = 3;
+                self.upgrade()
+            }
+            3 => {
+                self.schema_version = 4;
+                self.upgrade()
+            }
+            SCHEMA_VERSION => Ok(self),
+            version => Err(SchemaError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+impl GetScoresResponseDataItem {
+    /// Decodes a score of any supported version, upgraded to the current one.
+    /// The version is checked first, since a newer score may not even fit
+    /// these types.
+    pub fn from_value(value: Value) -> Result<GetScoresResponseDataItem, SchemaError> {
+        match value.get("schema_version").and_then(Value::as_u64) {
+            Some(version) if version > u64::from(SCHEMA_VERSION) => {
+                Err(SchemaError::UnsupportedVersion(version as u32))
+            }
+            _ => serde_json::from_value::<GetScoresResponseDataItem>(value)?.upgrade(),
+        }
+    }
+
+    pub fn upgrade(mut self) -> Result<GetScoresResponseDataItem, SchemaError> {
+        self.payload = self.payload.upgrade()?;
+        Ok(self)
+    }
+}
+
+/// Splits stored scores into the ones this build reads, upgraded, and the
+/// ones written by a newer build, left as they are. Other errors fail the
+/// whole list.
+pub fn partition_scores(
+    values: Vec<Value>,
+) -> Result<(Vec<GetScoresResponseDataItem>, Vec<Value>), SchemaError> {
+    let mut scores = vec![];
+    let mut newer = vec![];
+
+    for value in values {
+        match GetScoresResponseDataItem::from_value(value.clone()) {
+            Ok(score) => scores.push(score),
+            Err(SchemaError::UnsupportedVersion(_)) => newer.push(value),
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok((scores, newer))
+}
+
+/// A scores response before its scores are decoded one by one.
+#[derive(Deserialize)]
+struct RawScores {
+    scores: Vec<Value>,
+}
+
+impl GetScoresResponseData {
+    /// Decodes a scores response, upgrading every score in it. Scores from a
+    /// newer schema are left out, so one of them does not hide the others.
+    pub fn decode(json: &str) -> Result<GetScoresResponseData, SchemaError> {
+        let raw = serde_json::from_str::<RawScores>(json)?;
+        let (scores, _) = partition_scores(raw.scores)?;
+        Ok(GetScoresResponseData { scores })
+    }
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    fn payload() -> SendResultPayload {
+        SendResultPayload {
+            schema_version: SCHEMA_VERSION,
+            game_state: SerializedGameState {
+                cellules: "0120".to_owned(),
+                active: false,
+                cellules_width: 2,
+                cellules_height: 2,
+            },
+            step_count: 120,
+            active_count: 7,
+            modifications: vec![GridModification {
+                step_index: 0,
+                grid_index: 3,
+            }],
+            seed_label: "Glider".to_owned(),
+            user_name: "ABCD".to_owned(),
+            topology: Topology::Infinite,
+            rule: Rule::parse("B2/S345/C4").unwrap(),
+            geometry: Geometry::Hexagonal,
+            checkpoints: vec![Checkpoint {
+                step_index: 120,
+                hash: "00000000000000ff".to_owned(),
+                cellules: "0120".to_owned(),
+            }],
+            edit_budget: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payload = payload();
+        let encoded = payload.encode();
+
+        assert_eq!(SendResultPayload::decode(&encoded), Ok(payload));
+        assert!(encoded.contains(r#""schema_version":4"#));
+        assert!(encoded.contains(r#""step_count":120"#));
+        assert!(encoded.contains(r#""user_name":"ABCD""#));
+        assert!(encoded.contains(r#""rule":"B2/S345/C4""#));
+    }
+
+    #[test]
+    fn test_scores_round_trip() {
+        let data = GetScoresResponseData {
+            scores: vec![GetScoresResponseDataItem {
+                payload: payload(),
+                id: "5f1a".to_owned(),
+            }],
+        };
+        let encoded = serde_json::to_string(&data).unwrap();
+
+        assert!(encoded.contains(r#""_id":"5f1a""#));
+        assert_eq!(GetScoresResponseData::decode(&encoded), Ok(data));
+    }
+
+    #[test]
+    fn test_version_one_is_upgraded() {
+        let version_one = r#"{
+            "game_state": {"cellules": "0110", "active": false, "cellules_width": 2, "cellules_height": 2},
+            "step_count": 12,
+            "active_count": 2,
+            "modifications": [{"step_index": 0, "grid_index": 1}],
+            "seed_label": "Glider",
+            "user_name": "ABCD"
+        }"#;
+
+        let payload = SendResultPayload::decode(version_one).unwrap();
+        assert_eq!(payload.schema_version, SCHEMA_VERSION);
+        assert_eq!(payload.step_count, 12);
+        assert_eq!(payload.topology, Topology::Bounded);
+        assert!(payload.rule.is_conway());
+        assert_eq!(payload.geometry, Geometry::Square);
+        assert!(payload.checkpoints.is_empty());
+        assert_eq!(payload.edit_budget, None);
+    }
+
+    #[test]
+    fn test_newer_versions_are_refused() {
+        let mut newer = payload();
+        newer.schema_version = SCHEMA_VERSION + 1;
+
+        assert_eq!(
+            SendResultPayload::decode(&newer.encode()),
+            Err(SchemaError::UnsupportedVersion(SCHEMA_VERSION + 1))
+        );
+        assert!(matches!(
+            SendResultPayload::decode(r#"{"step_count": "12"}"#),
+            Err(SchemaError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_newer_scores_are_left_out() {
+        let item = |payload: SendResultPayload, id: &str| GetScoresResponseDataItem {
+            payload,
+            id: id.to_owned(),
+        };
+        let mut newer = payload();
+        newer.schema_version = SCHEMA_VERSION + 1;
+        let mut encoded = serde_json::to_value(&GetScoresResponseData {
+            scores: vec![item(payload(), "1"), item(newer, "2"), item(payload(), "3")],
+        })
+        .unwrap();
+        // A newer score may not fit today's types at all.
+        encoded["scores"][1]["rule"] = Value::from(7);
+
+        let data = GetScoresResponseData::decode(&encoded.to_string()).unwrap();
+        assert_eq!(
+            data.scores
+                .iter()
+                .map(|score| score.id.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["1", "3"]
+        );
+
+        let (scores, newer) =
+            partition_scores(encoded["scores"].as_array().unwrap().clone()).unwrap();
+        assert_eq!(scores.len(), 2);
+        assert_eq!(newer, vec![encoded["scores"][1].clone()]);
+
+        encoded["scores"][0]["step_count"] = Value::from("12");
+        assert!(matches!(
+            GetScoresResponseData::decode(&encoded.to_string()),
+            Err(SchemaError::Json(_))
+        ));
+    }
+}
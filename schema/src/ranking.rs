@@ -0,0 +1,336 @@
+//! Who places where on the leaderboards.
+//!
+//! Scores only compete with runs of the same seed, edits, topology, rule and
+//! geometry, where runs with an edit budget compete with runs of the same
+//! budget and other runs with runs of the same edit count. Daily Challenge
+//! runs compete with every run of the same day, since everyone there plays
+//! under the same edit budget. Within those, the Life board ranks the longest
+//! runs first with more active cellules breaking ties, and the Death board
+//! ranks the fewest active cellules first with fewer steps breaking ties. A
+//! new score has to beat an equal one to place ahead of it.
+
+use std::cmp::Ordering;
+
+use cellule_life_engine::daily::DailyChallenge;
+use cellule_life_engine::geometry::Geometry;
+use cellule_life_engine::rules::Rule;
+use cellule_life_engine::Topology;
+
+use crate::SendResultPayload;
+
+/// How many places each leaderboard shows.
+pub const TOP_N: usize = 20;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Leaderboard {
+    Life,
+    Death,
+}
+
+/// What a run scored.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Score {
+    pub step_count: i32,
+    pub active_count: i32,
+}
+
+/// How a run's edits group it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Edits {
+    Count(usize),
+    Budget(usize),
+}
+
+/// Runs with equal keys compete with each other.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeaderboardKey {
+    Seed {
+        seed_label: String,
+        edits: Edits,
+        topology: Topology,
+        rule: Rule,
+        geometry: Geometry,
+    },
+    Daily(DailyChallenge),
+}
+
+/// A leaderboard cut off after `top_n` places.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ranking {
+    pub leaderboard: Leaderboard,
+    pub top_n: usize,
+}
+
+impl Score {
+    pub fn new(step_count: i32, active_count: i32) -> Score {
+        Score {
+            step_count,
+            active_count,
+        }
+    }
+
+    pub fn of(payload: &SendResultPayload) -> Score {
+        Score {
+            step_count: payload.step_count,
+            active_count: payload.active_count,
+        }
+    }
+}
+
+impl LeaderboardKey {
+    pub fn of(payload: &SendResultPayload) -> LeaderboardKey {
+        LeaderboardKey::new(
+            &payload.seed_label,
+            payload.edit_budget,
+            payload.modifications.len(),
+            payload.topology,
+            &payload.rule,
+            payload.geometry,
+        )
+    }
+
+    /// The key of a run from its settings, for runs that are not payloads.
+    pub fn new(
+        seed_label: &str,
+        edit_budget: Option<usize>,
+        edit_count: usize,
+        topology: Topology,
+        rule: &Rule,
+        geometry: Geometry,
+    ) -> LeaderboardKey {
+        match DailyChallenge::parse(seed_label) {
+            Some(daily) => LeaderboardKey::Daily(daily),
+            None => LeaderboardKey::Seed {
+                seed_label: seed_label.to_owned(),
+                edits: match edit_budget {
+                    Some(budget) => Edits::Budget(budget),
+                    None => Edits::Count(edit_count),
+                },
+                topology,
+                rule: rule.clone(),
+                geometry,
+            },
+        }
+    }
+}
+
+impl Leaderboard {
+    /// `Less` when `a` ranks ahead of `b`.
+    pub fn compare(&self, a: &Score, b: &Score) -> Ordering {
+        match self {
+            Leaderboard::Life => b
+                .step_count
+                .cmp(&a.step_count)
+                .then(b.active_count.cmp(&a.active_count)),
+            Leaderboard::Death => a
+                .active_count
+                .cmp(&b.active_count)
+                .then(a.step_count.cmp(&b.step_count)),
+        }
+    }
+}
+
+impl Ranking {
+    pub fn new(leaderboard: Leaderboard) -> Ranking {
+        Ranking {
+            leaderboard,
+            top_n: TOP_N,
+        }
+    }
+
+    pub fn with_top_n(mut self, top_n: usize) -> Ranking {
+        self.top_n = top_n;
+        self
+    }
+
+    /// The shown places, best first. Equal scores keep their order.
+    pub fn top(&self, scores: &[Score]) -> Vec<Score> {
+        let mut sorted = scores.to_vec();
+        sorted.sort_by(|a, b| self.leaderboard.compare(a, b));
+        sorted.truncate(self.top_n);
+        sorted
+    }
+
+    /// The place, counting from 1, that `score` would take among `scores`,
+    /// or `None` if it would not make the top `top_n`.
+    pub fn projected_rank(&self, scores: &[Score], score: &Score) -> Option<usize> {
+        let ahead = scores
+            .iter()
+            .filter(|other| self.leaderboard.compare(other, score) != Ordering::Greater)
+            .count();
+
+        if ahead < self.top_n {
+            Some(ahead + 1)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::GridModification;
+
+    const PAYLOAD: &str = r#"{
+        "game_state": {"cellules": "0110", "active": false, "cellules_width": 2, "cellules_height": 2},
+        "step_count": 12,
+        "active_count": 2,
+        "modifications": [],
+        "seed_label": "Glider",
+        "user_name": "ABCD"
+    }"#;
+
+    fn board() -> Vec<Score> {
+        vec![
+            Score::new(100, 5),
+            Score::new(300, 2),
+            Score::new(300, 9),
+            Score::new(50, 0),
+        ]
+    }
+
+    #[test]
+    fn test_top_orders_by_primary_then_secondary_key() {
+        let cases = vec![
+            (
+                Leaderboard::Life,
+                vec![
+                    Score::new(300, 9),
+                    Score::new(300, 2),
+                    Score::new(100, 5),
+                    Score::new(50, 0),
+                ],
+            ),
+            (
+                Leaderboard::Death,
+                vec![
+                    Score::new(50, 0),
+                    Score::new(300, 2),
+                    Score::new(100, 5),
+                    Score::new(300, 9),
+                ],
+            ),
+        ];
+
+        for (leaderboard, expected) in cases {
+            assert_eq!(Ranking::new(leaderboard).top(&board()), expected);
+        }
+    }
+
+    #[test]
+    fn test_top_is_cut_off_after_top_n() {
+        let cases = vec![(0, 0), (2, 2), (4, 4), (10, 4)];
+
+        for (top_n, expected_len) in cases {
+            let ranking = Ranking::new(Leaderboard::Life).with_top_n(top_n);
+            assert_eq!(ranking.top(&board()).len(), expected_len, "top {}", top_n);
+        }
+    }
+
+    #[test]
+    fn test_projected_rank() {
+        let cases = vec![
+            // (leaderboard, top_n, score, expected rank)
+            (Leaderboard::Life, 20, Score::new(400, 0), Some(1)),
+            (Leaderboard::Life, 20, Score::new(300, 10), Some(1)),
+            (Leaderboard::Life, 20, Score::new(300, 9), Some(2)),
+            (Leaderboard::Life, 20, Score::new(300, 5), Some(2)),
+            (Leaderboard::Life, 20, Score::new(300, 2), Some(3)),
+            (Leaderboard::Life, 20, Score::new(10, 0), Some(5)),
+            (Leaderboard::Life, 4, Score::new(10, 0), None),
+            (Leaderboard::Life, 4, Score::new(60, 0), Some(4)),
+            (Leaderboard::Life, 0, Score::new(400, 0), None),
+            (Leaderboard::Death, 20, Score::new(10, 0), Some(1)),
+            (Leaderboard::Death, 20, Score::new(50, 0), Some(2)),
+            (Leaderboard::Death, 20, Score::new(200, 2), Some(2)),
+            (Leaderboard::Death, 20, Score::new(1000, 100), Some(5)),
+            (Leaderboard::Death, 3, Score::new(90, 5), Some(3)),
+            (Leaderboard::Death, 3, Score::new(100, 5), None),
+        ];
+
+        for (leaderboard, top_n, candidate, expected) in cases {
+            let ranking = Ranking::new(leaderboard).with_top_n(top_n);
+            assert_eq!(
+                ranking.projected_rank(&board(), &candidate),
+                expected,
+                "{:?} top {} with {:?}",
+                leaderboard,
+                top_n,
+                candidate
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_leaderboards_take_anyone() {
+        for leaderboard in [Leaderboard::Life, Leaderboard::Death] {
+            let ranking = Ranking::new(leaderboard);
+            assert_eq!(ranking.projected_rank(&[], &Score::new(0, 0)), Some(1));
+            assert!(ranking.top(&[]).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_daily_runs_share_a_board_whatever_their_edits() {
+        let daily = |modifications: Vec<GridModification>| SendResultPayload {
+            seed_label: "Daily 2026-10-18".to_owned(),
+            modifications,
+            ..SendResultPayload::decode(PAYLOAD).unwrap()
+        };
+        let edit = GridModification {
+            step_index: 0,
+            grid_index: 7,
+        };
+
+        let daily_key = LeaderboardKey::of(&daily(vec![]));
+        assert_eq!(
+            daily_key,
+            LeaderboardKey::Daily(DailyChallenge::new(2026, 10, 18).unwrap())
+        );
+        assert_eq!(LeaderboardKey::of(&daily(vec![edit, edit])), daily_key);
+
+        let seeded = SendResultPayload::decode(PAYLOAD).unwrap();
+        let mut edited = seeded.clone();
+        edited.modifications.push(edit);
+        assert_ne!(LeaderboardKey::of(&seeded), LeaderboardKey::of(&edited));
+        assert_ne!(LeaderboardKey::of(&seeded), daily_key);
+    }
+
+    #[test]
+    fn test_budgeted_runs_are_grouped_by_budget() {
+        let run = |edit_budget: Option<usize>, edit_count: i32| {
+            let mut payload = SendResultPayload::decode(PAYLOAD).unwrap();
+            payload.edit_budget = edit_budget;
+            payload.modifications = (0..edit_count)
+                .map(|grid_index| GridModification {
+                    step_index: 0,
+                    grid_index,
+                })
+                .collect();
+            LeaderboardKey::of(&payload)
+        };
+
+        let cases = vec![
+            // (a, b, same leaderboard)
+            ((Some(5), 1), (Some(5), 4), true),
+            ((Some(5), 3), (Some(3), 3), false),
+            ((None, 3), (None, 3), true),
+            ((None, 3), (None, 2), false),
+            ((None, 3), (Some(3), 3), false),
+        ];
+
+        for ((budget_a, edits_a), (budget_b, edits_b), same) in cases {
+            assert_eq!(
+                run(budget_a, edits_a) == run(budget_b, edits_b),
+                same,
+                "{:?}/{} against {:?}/{}",
+                budget_a,
+                edits_a,
+                budget_b,
+                edits_b
+            );
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! A local stand-in for the scores API.
+//!
+//! Serves `get-high-scores` and `submit-result` in the shared wire schema and
+//! keeps every submitted score in a JSON file, so the whole submit and fetch
+//! flow can run without any external service. Older payloads are upgraded
+//! to the current schema before they are stored; scores from a newer schema
+//! are kept in the file but not served.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tiny_http::{Header, Response, Server};
+
+use cellule_life_engine::daily::DailyChallenge;
+use cellule_life_schema::{
+    partition_scores, GetScoresResponseData, GetScoresResponseDataItem, ResultResponseData,
+    SendResultPayload,
+};
+use serde_json::Value;
+
+/// Scores kept in a JSON file, rewritten on every submit.
+pub struct ScoreStore {
+    path: PathBuf,
+    scores: Vec<GetScoresResponseDataItem>,
+    /// Scores written by a newer build, kept as they are.
+    newer_scores: Vec<Value>,
+}
+
+impl ScoreStore {
+    /// Opens the store at `path`. A missing file is an empty store.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<ScoreStore> {
+        let path = path.as_ref().to_path_buf();
+        let (scores, newer_scores) = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str::<Vec<Value>>(&contents)
+                .map_err(|error| error.to_string())
+                .and_then(|scores| partition_scores(scores).map_err(|error| error.to_string()))
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (vec![], vec![]),
+            Err(error) => return Err(error),
+        };
+
+        Ok(ScoreStore {
+            path,
+            scores,
+            newer_scores,
+        })
+    }
+
+    pub fn scores(&self) -> &[GetScoresResponseDataItem] {
+        &self.scores
+    }
+
+    pub fn insert(&mut self, payload: SendResultPayload) -> io::Result<&GetScoresResponseDataItem> {
+        let id = format!("{:024x}", self.scores.len() + self.newer_scores.len() + 1);
+        self.scores.push(GetScoresResponseDataItem { payload, id });
+        // A score that could not be written is not served either.
+        if let Err(error) = self.save() {
+            self.scores.pop();
+            return Err(error);
+        }
+
+        Ok(self.scores.last().unwrap())
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let mut scores = self
+            .scores
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<Value>, _>>()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        scores.extend(self.newer_scores.iter().cloned());
+        let contents = serde_json::to_string_pretty(&scores)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(&self.path, contents)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl ApiResponse {
+    fn json<T: serde::Serialize>(status: u16, body: &T) -> ApiResponse {
+        ApiResponse {
+            status,
+            body: serde_json::to_string(body).unwrap(),
+        }
+    }
+
+    fn message(status: u16, message: String) -> ApiResponse {
+        ApiResponse::json(status, &ResultResponseData { message })
+    }
+}
+
+/// Answers one request. Only the last path segment is matched, so the
+/// `/prod/...` URLs from `.environment` work as they are.
+pub fn handle(store: &mut ScoreStore, method: &str, url: &str, body: &str) -> ApiResponse {
+    let path = url.split('?').next().unwrap_or("");
+    let endpoint = path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+
+    match (method, endpoint) {
+        // CORS preflight from the dev server on another port.
+        ("OPTIONS", _) => ApiResponse {
+            status: 204,
+            body: String::new(),
+        },
+        ("GET", "get-high-scores") => ApiResponse::json(
+            200,
+            &GetScoresResponseData {
+                scores: store.scores().to_vec(),
+            },
+        ),
+        ("POST", "submit-result") => match SendResultPayload::decode(body) {
+            Ok(payload) if exceeds_edit_budget(&payload) => {
+                ApiResponse::message(400, "the run uses more edits than its budget".to_owned())
+            }
+            Ok(payload) if is_future_daily(&payload, today()) => {
+                ApiResponse::message(400, "that Daily Challenge has not started yet".to_owned())
+            }
+            Ok(payload) => match store.insert(payload) {
+                Ok(score) => ApiResponse::message(200, format!("stored score {}", score.id)),
+                Err(error) => {
+                    ApiResponse::message(500, format!("could not store score: {}", error))
+                }
+            },
+            Err(error) => ApiResponse::message(400, format!("invalid result: {}", error)),
+        },
+        (_, "get-high-scores") | (_, "submit-result") => {
+            ApiResponse::message(405, format!("{} is not allowed on {}", method, path))
+        }
+        _ => ApiResponse::message(404, format!("no endpoint at {}", path)),
+    }
+}
+
+/// Boards are rebuilt from their seed label wherever they are needed, so only
+/// edit budgets, the run's own and the Daily Challenge's, are worth refusing
+/// at the door.
+fn exceeds_edit_budget(payload: &SendResultPayload) -> bool {
+    let daily_budget = DailyChallenge::parse(&payload.seed_label).map(|daily| daily.edit_budget());
+
+    [payload.edit_budget, daily_budget]
+        .iter()
+        .flatten()
+        .any(|budget| payload.modifications.len() > *budget)
+}
+
+/// Whether the run is a Daily Challenge of a day after `today`.
+fn is_future_daily(payload: &SendResultPayload, today: DailyChallenge) -> bool {
+    DailyChallenge::parse(&payload.seed_label).is_some_and(|daily| daily > today)
+}
+
+/// The Daily Challenge of the current UTC day.
+fn today() -> DailyChallenge {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    DailyChallenge::from_unix_days((seconds / 86_400) as i64)
+}
+
+/// Serves requests until the server shuts down.
+pub fn serve(server: Server, mut store: ScoreStore) {
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => handle(&mut store, request.method().as_str(), request.url(), &body),
+            Err(error) => ApiResponse::message(400, format!("unreadable body: {}", error)),
+        };
+
+        println!(
+            "{} {} -> {}",
+            request.method(),
+            request.url(),
+            response.status
+        );
+
+        let response = Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(header("Content-Type", "application/json"))
+            .with_header(header("Access-Control-Allow-Origin", "*"))
+            .with_header(header("Access-Control-Allow-Methods", "GET, POST, OPTIONS"))
+            .with_header(header("Access-Control-Allow-Headers", "Content-Type"));
+
+        if let Err(error) = request.respond(response) {
+            eprintln!("could not respond: {}", error);
+        }
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use cellule_life_schema::SCHEMA_VERSION;
+
+    fn temp_store(name: &str) -> ScoreStore {
+        let path =
+            std::env::temp_dir().join(format!("cellule-life-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        ScoreStore::open(path).unwrap()
+    }
+
+    const PAYLOAD: &str = r#"{
+        "game_state": {"cellules": "0110", "active": false, "cellules_width": 2, "cellules_height": 2},
+        "step_count": 12,
+        "active_count": 2,
+        "modifications": [{"step_index": 0, "grid_index": 1}],
+        "seed_label": "Glider",
+        "user_name": "ABCD",
+        "rule": "B2/S/C3"
+    }"#;
+
+    #[test]
+    fn test_submit_then_fetch() {
+        let mut store = temp_store("submit");
+
+        let submitted = handle(&mut store, "POST", "/prod/submit-result", PAYLOAD);
+        assert_eq!(submitted.status, 200);
+
+        let fetched = handle(&mut store, "GET", "/prod/get-high-scores", "");
+        assert_eq!(fetched.status, 200);
+        let data: GetScoresResponseData = serde_json::from_str(&fetched.body).unwrap();
+        assert_eq!(data.scores.len(), 1);
+        assert_eq!(data.scores[0].payload.seed_label, "Glider");
+        assert_eq!(data.scores[0].payload.rule.to_string(), "B2/S/C3");
+        assert_eq!(data.scores[0].payload.schema_version, SCHEMA_VERSION);
+        assert!(fetched.body.contains(r#""_id":"#));
+    }
+
+    #[test]
+    fn test_scores_survive_reopening() {
+        let mut store = temp_store("reopen");
+        handle(&mut store, "POST", "/submit-result", PAYLOAD);
+
+        let reopened = ScoreStore::open(&store.path).unwrap();
+        assert_eq!(reopened.scores(), store.scores());
+    }
+
+    #[test]
+    fn test_newer_scores_are_kept_but_not_served() {
+        let mut store = temp_store("newer");
+        let newer = PAYLOAD.replacen('{', r#"{"schema_version": 99, "_id": "9","#, 1);
+        fs::write(&store.path, format!("[{}]", newer)).unwrap();
+        store = ScoreStore::open(&store.path).unwrap();
+        assert!(store.scores().is_empty());
+
+        handle(&mut store, "POST", "/submit-result", PAYLOAD);
+        let fetched = handle(&mut store, "GET", "/get-high-scores", "");
+        let data: GetScoresResponseData = serde_json::from_str(&fetched.body).unwrap();
+        assert_eq!(data.scores.len(), 1);
+
+        let reopened = ScoreStore::open(&store.path).unwrap();
+        assert_eq!(reopened.scores(), store.scores());
+        assert_eq!(reopened.newer_scores.len(), 1);
+        assert_eq!(reopened.newer_scores[0]["schema_version"], 99);
+    }
+
+    #[test]
+    fn test_scores_that_cannot_be_written_are_not_kept() {
+        let path = std::env::temp_dir()
+            .join(format!("cellule-life-missing-{}", std::process::id()))
+            .join("scores.json");
+        let mut store = ScoreStore::open(path).unwrap();
+
+        let submitted = handle(&mut store, "POST", "/submit-result", PAYLOAD);
+        assert_eq!(submitted.status, 500);
+        assert!(store.scores().is_empty());
+
+        let fetched = handle(&mut store, "GET", "/get-high-scores", "");
+        let data: GetScoresResponseData = serde_json::from_str(&fetched.body).unwrap();
+        assert!(data.scores.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_requests() {
+        let mut store = temp_store("reject");
+
+        assert_eq!(
+            handle(&mut store, "POST", "/submit-result", "{}").status,
+            400
+        );
+        assert_eq!(handle(&mut store, "GET", "/submit-result", "").status, 405);
+        assert_eq!(handle(&mut store, "GET", "/nowhere", "").status, 404);
+        assert_eq!(
+            handle(&mut store, "OPTIONS", "/submit-result", "").status,
+            204
+        );
+        assert!(store.scores().is_empty());
+    }
+
+    #[test]
+    fn test_edit_budgets_are_enforced() {
+        let mut store = temp_store("daily");
+        let edits = |count: i32| {
+            (0..count)
+                .map(|index| format!(r#"{{"step_index": 0, "grid_index": {}}}"#, index))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let daily = |count: i32| {
+            PAYLOAD
+                .replace(
+                    r#""seed_label": "Glider""#,
+                    r#""seed_label": "Daily 2026-10-18""#,
+                )
+                .replace(
+                    r#""modifications": [{"step_index": 0, "grid_index": 1}]"#,
+                    &format!(r#""modifications": [{}]"#, edits(count)),
+                )
+        };
+
+        assert_eq!(
+            handle(&mut store, "POST", "/submit-result", &daily(6)).status,
+            400
+        );
+        assert_eq!(
+            handle(&mut store, "POST", "/submit-result", &daily(5)).status,
+            200
+        );
+        assert_eq!(store.scores()[0].payload.modifications.len(), 5);
+
+        let over_own_budget = PAYLOAD.replace(
+            r#""rule": "B2/S/C3""#,
+            r#""rule": "B2/S/C3", "edit_budget": 0"#,
+        );
+        assert_eq!(
+            handle(&mut store, "POST", "/submit-result", &over_own_budget).status,
+            400
+        );
+    }
+
+    #[test]
+    fn test_future_daily_challenges_are_refused() {
+        let mut store = temp_store("future");
+        let on = |label: &str| {
+            PAYLOAD.replace(
+                r#""seed_label": "Glider""#,
+                &format!(r#""seed_label": "{}""#, label),
+            )
+        };
+
+        assert_eq!(
+            handle(
+                &mut store,
+                "POST",
+                "/submit-result",
+                &on("Daily 2999-01-01")
+            )
+            .status,
+            400
+        );
+        assert!(store.scores().is_empty());
+
+        let payload = SendResultPayload::decode(&on("Daily 2026-10-19")).unwrap();
+        let today = DailyChallenge::new(2026, 10, 18).unwrap();
+        assert!(is_future_daily(&payload, today));
+        assert!(!is_future_daily(
+            &SendResultPayload::decode(&on("Daily 2026-10-18")).unwrap(),
+            today
+        ));
+        assert!(!is_future_daily(
+            &SendResultPayload::decode(PAYLOAD).unwrap(),
+            today
+        ));
+    }
+}
@@ -0,0 +1,38 @@
+use cellule_life_mock_server::{serve, ScoreStore};
+use std::env;
+use std::process;
+use tiny_http::Server;
+
+const USAGE: &str = "usage: cellule-life-mock-server [--port 3100] [--store scores.json]";
+
+fn main() {
+    let mut port = 3100;
+    let mut store_path = String::from("scores.json");
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--port", Some(value)) => port = value.parse().unwrap_or_else(|_| exit(USAGE)),
+            ("--store", Some(value)) => store_path = value,
+            _ => exit(USAGE),
+        }
+    }
+
+    let store = ScoreStore::open(&store_path)
+        .unwrap_or_else(|error| exit(&format!("could not open {}: {}", store_path, error)));
+    let server = Server::http(("127.0.0.1", port))
+        .unwrap_or_else(|error| exit(&format!("could not listen on port {}: {}", port, error)));
+
+    println!(
+        "serving {} scores from {} on http://localhost:{}",
+        store.scores().len(),
+        store_path,
+        port
+    );
+    serve(server, store);
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
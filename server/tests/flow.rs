@@ -0,0 +1,67 @@
+//! Drives the mock server over a real socket, the way the web client does.
+
+use cellule_life_mock_server::{serve, ScoreStore};
+use cellule_life_schema::GetScoresResponseData;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use tiny_http::Server;
+
+fn start_server() -> SocketAddr {
+    let path = std::env::temp_dir().join(format!("cellule-life-flow-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let store = ScoreStore::open(path).unwrap();
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let address = server.server_addr().to_ip().unwrap();
+    thread::spawn(move || serve(server, store));
+
+    address
+}
+
+fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_owned())
+        .unwrap_or_default();
+    (status, body)
+}
+
+#[test]
+fn test_submit_and_fetch_over_http() {
+    let address = start_server();
+    let payload = r#"{
+        "game_state": {"cellules": "01", "active": false, "cellules_width": 2, "cellules_height": 1},
+        "step_count": 40,
+        "active_count": 1,
+        "modifications": [],
+        "seed_label": "Blinker",
+        "user_name": "WXYZ"
+    }"#;
+
+    let (status, _) = request(address, "POST", "/prod/submit-result", payload);
+    assert_eq!(status, 200);
+
+    let (status, body) = request(address, "GET", "/prod/get-high-scores", "");
+    assert_eq!(status, 200);
+
+    let data: GetScoresResponseData = serde_json::from_str(&body).unwrap();
+    assert_eq!(data.scores.len(), 1);
+    assert_eq!(data.scores[0].payload.user_name, "WXYZ");
+    assert_eq!(data.scores[0].payload.step_count, 40);
+}
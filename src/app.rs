@@ -1,21 +1,62 @@
+mod api;
+mod board_window;
+mod cell_ages;
 mod components;
+mod heat_map;
+mod population;
+mod preferences;
+mod run_history;
+mod slots;
+mod storage;
 
-use anyhow::Error;
 use log::*;
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
-use wasm_bindgen::{closure::Closure, convert::IntoWasmAbi, prelude::wasm_bindgen, JsValue};
-use yew::format::{Json, Nothing};
+use wasm_bindgen::{
+    closure::Closure, convert::IntoWasmAbi, prelude::wasm_bindgen, JsCast, JsValue,
+};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 use yew::prelude::*;
-use yew::services::fetch::{FetchService, FetchTask, Request, Response};
-use yew::services::storage::{Area, StorageService};
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::services::storage::Area;
 use yew::services::{RenderService, Task};
 use yew::virtual_dom::vlist::VList;
 use yew::virtual_dom::vnode::VNode;
 
+use crate::app::api::{ApiClient, ApiError, ApiTask};
+use crate::app::cell_ages::CellAges;
+use crate::app::components::chart::PopulationChart;
 use crate::app::components::fps::FpsDetector;
-use crate::app::components::grid::GameGrid;
+use crate::app::components::grid::{ColorMode, GameGrid};
 use crate::app::components::header::AppHeader;
+use crate::app::components::slots::SlotManager;
+use crate::app::components::stats::StatsView;
+use crate::app::heat_map::HeatMap;
+use crate::app::population::PopulationHistory;
+use crate::app::preferences::Preferences;
+use crate::app::run_history::{record_run, restore_runs, EndReason, PastRun};
+use crate::app::slots::{
+    next_slot_id, remove_slot, restore_slots, store_slot, SaveSlot, SLOT_VERSION,
+};
+use crate::app::storage::{unversioned, Schema, VersionedStorage};
+use crate::utils::colors::{Palette, HEAT_RAMP};
+use crate::utils::theme::Theme;
+use cellule_life_engine::census::{take_census, CensusEntry};
+use cellule_life_engine::daily::DailyChallenge;
+use cellule_life_engine::generations::GenerationsLife;
+use cellule_life_engine::geometry::Geometry;
+use cellule_life_engine::hashlife::HashLife;
+use cellule_life_engine::ltl::LargerThanLife;
+use cellule_life_engine::rules::{serialize_states, Rule};
+use cellule_life_engine::sparse::SparseLife;
+use cellule_life_engine::{
+    neighbor_map, sync_game_state, to_cellules, Engine, MultiStateEngine, Topology, MAX_STEP_COUNT,
+};
+use cellule_life_schema::ranking::{Leaderboard, LeaderboardKey, Ranking, Score};
+use cellule_life_schema::{
+    GetScoresResponseData, GetScoresResponseDataItem, GridModification, HashChain,
+    ResultResponseData, SendResultPayload, SerializedGameState, SCHEMA_VERSION,
+};
 
 // use crate::app::core::game::{Cellule, GameState, LifeState};
 // use crate::app::core::seeds::{seed_middle_line_starter, seed_pentadecathlon};
@@ -25,8 +66,22 @@ use game_of_life_core::core::history::History;
 #[allow(dead_code)]
 use game_of_life_core::core::seeds::seeds::{get_seeds, seed_middle_line_starter, Seed};
 
-const GRID_KEY: &str = "yew.gameofdeath.grid";
-const INTRO_MODAL_KEY: &str = "yew.gameofdeath.showing_intro_modal";
+const SCORES_KEY: &str = "yew.gameofdeath.scores";
+const SUBMISSION_QUEUE_KEY: &str = "yew.gameofdeath.submission_queue";
+
+const SCORES_SCHEMA: Schema = Schema {
+    version: 1,
+    migrations: &[unversioned],
+};
+// Payloads in the queue also carry their own schema version and are upgraded
+// after loading.
+const SUBMISSION_QUEUE_SCHEMA: Schema = Schema {
+    version: 1,
+    migrations: &[unversioned],
+};
+const CONNECTION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// Pixels per cellule in an exported heat map.
+const HEAT_MAP_PNG_SCALE: usize = 10;
 
 struct EnvVars {
     API_URL_SUBMIT_RESULT: String,
@@ -35,60 +90,80 @@ struct EnvVars {
 
 pub struct App {
     link: ComponentLink<Self>,
-    storage: StorageService,
+    storage: VersionedStorage,
     state: State,
     #[allow(unused)]
     render_loop: Option<Box<dyn Task>>,
-    send_result_fetch_task: Option<FetchTask>,
-    fetch_scores_fetch_task: Option<FetchTask>,
+    send_result_fetch_task: Option<ApiTask>,
+    fetch_scores_fetch_task: Option<ApiTask>,
+    submission_queue: Vec<SendResultPayload>,
+    connection_check: Option<IntervalTask>,
     history: History,
     last_render_timestamp: f64,
     seed_options: Vec<Seed>,
-    env_vars: EnvVars,
+    api: ApiClient,
     max_fps: i64,
     previous_scores: Vec<GetScoresResponseDataItem>,
-    showing_intro_modal: bool,
-}
-
-#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
-pub struct GridModification {
-    step_index: i32,
-    grid_index: i32,
+    preferences: Preferences,
+    sparse: Option<SparseLife>,
+    generations: Option<Box<dyn MultiStateEngine>>,
+    hash_chain: Option<HashChain>,
+    save_slots: Vec<SaveSlot>,
+    showing_slot_manager: bool,
+    past_runs: Vec<PastRun>,
+    showing_stats: bool,
+    /// Whether the run on the grid is a replay of a past run.
+    is_replay: bool,
+    population_history: PopulationHistory,
+    cell_ages: CellAges,
+    heat_map: HeatMap,
+    showing_heat_map: bool,
+    /// Whether the system asks for a dark color scheme.
+    prefers_dark: bool,
+    /// Only held so the listener lives as long as the app.
+    #[allow(dead_code)]
+    color_scheme_listener: Option<Closure<dyn FnMut(web_sys::Event)>>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct State {
-    grid: Vec<Vec<Pixel>>,
     game_state: GameState,
     is_playing: bool,
+    is_paused: bool,
     is_started: bool,
     step_count: i32,
     active_count: i32,
     modifications: Vec<GridModification>,
     current_seed: Seed,
-    has_life_high_score: bool,
-    has_death_high_score: bool,
+    life_rank: Option<usize>,
+    death_rank: Option<usize>,
     user_name: String,
     has_no_network: bool,
+    submit_error: Option<String>,
     user_name_is_valid: bool,
     rate: f64,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Pixel {
-    on: bool,
+    is_analysis: bool,
+    topology: Topology,
+    rule: Rule,
+    geometry: Geometry,
+    edit_budget: Option<usize>,
+    /// The objects left on the board when the run ended.
+    census: Vec<CensusEntry>,
 }
 
 #[allow(dead_code)]
 pub enum Msg {
     GridClicked((i32, i32)),
-    HandleSendResultResponse(Result<ResultResponseData, Error>),
-    HandleGetScoresResponse(Result<GetScoresResponseData, Error>),
-    HandleGetScoresError,
+    HandleSendResultResponse(Result<ResultResponseData, ApiError>),
+    HandleGetScoresResponse(Result<GetScoresResponseData, ApiError>),
     SendResult,
+    CheckConnection,
+    RetrySubmit,
+    DiscardSubmit,
     Start,
     StepGame,
     Stop,
+    TogglePause,
     Render,
     HandleSeedChange(Seed),
     DismissScoreModal,
@@ -97,99 +172,66 @@ pub enum Msg {
     ChangeUserName(String),
     HandleRender,
     HandleRateChange(f64),
+    HandleColorModeChange(ColorMode),
+    HandlePaletteChange(Option<Palette>),
+    HandleThemeChange(Option<Theme>),
+    HandleColorSchemeChange,
     HandleFpsDetection(i64),
+    JumpToGeneration(u64),
+    HandleTopologyChange(Topology),
+    HandleRuleChange(Rule),
+    HandleGeometryChange(Geometry),
+    HandleEditBudgetChange(Option<usize>),
     DismissIntroModalClick,
+    ToggleSlotManager,
+    SaveSlot(String),
+    LoadSlot(u32),
+    RenameSlot((u32, String)),
+    DeleteSlot(u32),
+    ExportSlot(u32),
+    ToggleStats,
+    ToggleHeatMap,
+    ExportHeatMapPng,
+    ExportHeatMapCsv,
+    ReplayRun(usize),
     Nope,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ResultResponseData {
-    message: String,
-}
-
-impl From<SendResultPayload> for std::result::Result<std::string::String, anyhow::Error> {
-    fn from(payload: SendResultPayload) -> Self {
-        Ok(format!(
-            "{{
-                \"game_state\": \"{:?}\",
-                \"step_count\": \"{:?}\",
-                \"active_count\": \"{:?}\",
-                \"modifications\": \"{:?}\",
-                \"seed_label\": \"{:?}\"
-            }}",
-            payload.game_state,
-            payload.step_count,
-            payload.active_count,
-            payload.modifications,
-            payload.seed_label
-        )
-        .to_string())
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SendResultPayload {
-    game_state: SerializedGameState,
-    step_count: i32,
-    active_count: i32,
-    modifications: Vec<GridModification>,
-    seed_label: String,
-    user_name: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GetScoresResponseDataItem {
-    game_state: SerializedGameState,
-    step_count: i32,
-    active_count: i32,
-    modifications: Vec<GridModification>,
-    seed_label: String,
-    user_name: String,
-    _id: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GetScoresResponseData {
-    scores: Vec<GetScoresResponseDataItem>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SerializedGameState {
-    cellules: String,
-    pub active: bool,
-    pub cellules_width: usize,
-    pub cellules_height: usize,
-}
-
 impl Component for App {
     type Message = Msg;
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let storage = StorageService::new(Area::Local).unwrap();
-        let grid = {
-            if let Json(Ok(restored_grid)) = storage.restore(GRID_KEY) {
-                restored_grid
-            } else {
-                Vec::new()
-            }
-        };
+        let mut storage = VersionedStorage::new(Area::Local);
+        let preferences = Preferences::restore(&mut storage);
 
-        let showing_intro_modal = {
-            if let Json(Ok(restored_showing_intro_modal)) = storage.restore(INTRO_MODAL_KEY) {
-                restored_showing_intro_modal
-            } else {
-                true
-            }
-        };
+        // Offline the last leaderboard we saw stands in for the server's.
+        let previous_scores = storage
+            .restore(SCORES_KEY, &SCORES_SCHEMA)
+            .unwrap_or_default();
+
+        // Scores queued by an older build are upgraded to the current schema.
+        let submission_queue = storage
+            .restore::<Vec<SendResultPayload>>(SUBMISSION_QUEUE_KEY, &SUBMISSION_QUEUE_SCHEMA)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|payload| payload.upgrade().ok())
+            .collect();
+
+        let save_slots = restore_slots(&mut storage);
+        let past_runs = restore_runs(&mut storage);
 
         let history = History {
             previous_steps: vec![],
         };
 
-        let seed_options = get_seeds();
+        let seed_options = App::seed_options(Geometry::Square);
 
-        let current_seed = seed_options[0].clone();
+        let current_seed = seed_options
+            .iter()
+            .find(|seed| seed.label == preferences.seed_label)
+            .unwrap_or(&seed_options[0])
+            .clone();
 
         let game_state = GameState {
             active: false,
@@ -200,42 +242,73 @@ impl Component for App {
         };
 
         let state = State {
-            grid,
             game_state,
             is_playing: false,
+            is_paused: false,
             is_started: false,
             step_count: 0,
             active_count: 0,
             modifications: vec![],
             current_seed,
-            has_life_high_score: false,
-            has_death_high_score: false,
-            user_name: "".to_string(),
+            life_rank: None,
+            death_rank: None,
+            user_name: preferences.user_name.clone(),
             has_no_network: false,
-            user_name_is_valid: false,
-            rate: 60.0,
+            submit_error: None,
+            user_name_is_valid: is_valid_user_name(&preferences.user_name),
+            rate: preferences.rate,
+            is_analysis: false,
+            topology: Topology::Bounded,
+            rule: Rule::conway(),
+            geometry: Geometry::Square,
+            edit_budget: None,
+            census: vec![],
         };
 
-        App {
+        let mut app = App {
             link,
             storage,
             state,
             render_loop: None,
             send_result_fetch_task: None,
             fetch_scores_fetch_task: None,
+            submission_queue,
+            connection_check: None,
             history,
             last_render_timestamp: js_sys::Date::now(), //Instant::now(),
             seed_options,
-            env_vars: App::get_env_vars(),
+            api: ApiClient::new(App::get_env_vars()),
             max_fps: 60,
-            previous_scores: vec![],
-            showing_intro_modal,
-        }
+            previous_scores,
+            preferences,
+            sparse: None,
+            generations: None,
+            hash_chain: None,
+            save_slots,
+            showing_slot_manager: false,
+            past_runs,
+            showing_stats: false,
+            is_replay: false,
+            population_history: PopulationHistory::default(),
+            cell_ages: CellAges::default(),
+            heat_map: HeatMap::default(),
+            showing_heat_map: false,
+            prefers_dark: App::color_scheme_query().is_some_and(|query| query.matches()),
+            color_scheme_listener: None,
+        };
+
+        app.color_scheme_listener =
+            App::listen_to_color_scheme(app.link.callback(|_| Msg::HandleColorSchemeChange));
+        app.apply_theme();
+
+        // Sets up the restored seed the way picking it would.
+        app.update(Msg::HandleSeedChange(current_seed));
+        app
     }
 
     fn rendered(&mut self, first_render: bool) {
         if first_render {
-            self.fetch_scores_fetch_task = Some(self.fetch_scores());
+            self.fetch_scores();
             self.update(Msg::Render);
         }
 
@@ -249,52 +322,126 @@ impl Component for App {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::GridClicked((column_number, row_number)) => {
-                if self.state.is_started == false {
-                    let index =
-                        row_number * (self.state.game_state.cellules_width as i32) + column_number;
-
-                    match self
-                        .state
-                        .modifications
-                        .iter()
-                        .find(|&modification| modification.grid_index == index)
-                    {
+                if self.state.is_started == false || self.state.is_paused {
+                    let index = match self.board_index(column_number, row_number) {
+                        Some(index) => index,
+                        None => return false,
+                    };
+                    let step_index = self.state.step_count;
+
+                    // Toggling a cellule twice in the same generation undoes the edit.
+                    match self.state.modifications.iter().find(|&modification| {
+                        modification.grid_index == index && modification.step_index == step_index
+                    }) {
                         Some(_) => {
                             self.state.modifications = self
                                 .state
                                 .modifications
                                 .iter()
                                 .cloned()
-                                .filter(|modification| modification.grid_index != index)
+                                .filter(|modification| {
+                                    modification.grid_index != index
+                                        || modification.step_index != step_index
+                                })
                                 .collect::<Vec<GridModification>>()
                         }
+                        _ if self.is_out_of_edits() => return false,
                         _ => self.state.modifications.push(GridModification {
                             grid_index: index,
-                            step_index: self.state.step_count,
+                            step_index,
                         }),
                     }
 
-                    self.state.game_state.toggle_cellule(index as usize);
+                    self.toggle_cellule(index as usize);
                 }
             }
             Msg::HandleGetScoresResponse(get_scores_response) => {
-                self.previous_scores = get_scores_response.unwrap().scores;
+                self.fetch_scores_fetch_task = None;
+
+                match get_scores_response {
+                    Ok(data) => {
+                        self.previous_scores = data.scores;
+                        self.storage
+                            .store(SCORES_KEY, &SCORES_SCHEMA, &self.previous_scores);
+                        self.set_online(true);
+                        self.flush_submissions();
+                    }
+                    Err(error) => {
+                        warn!("could not load scores: {}", error);
+                        self.set_online(false);
+                    }
+                }
+            }
+            Msg::HandleSendResultResponse(send_result_response) => {
+                self.send_result_fetch_task = None;
 
-                self.state.has_no_network = false;
+                match send_result_response {
+                    Ok(_) => {
+                        if !self.submission_queue.is_empty() {
+                            self.submission_queue.remove(0);
+                            self.store_submission_queue();
+                        }
+                        self.state.submit_error = None;
+                        self.flush_submissions();
+                    }
+                    // Offline scores stay queued until the connection check
+                    // finds the server again.
+                    Err(ApiError::Network(_)) | Err(ApiError::Timeout) => {
+                        self.set_online(false);
+                    }
+                    Err(error) => {
+                        warn!("could not submit result: {}", error);
+                        self.state.submit_error = Some(error.to_string());
+                    }
+                }
             }
-            Msg::HandleGetScoresError => {
-                self.state.has_no_network = true;
+            Msg::CheckConnection => {
+                if self.fetch_scores_fetch_task.is_none() {
+                    self.fetch_scores();
+                }
             }
-            Msg::HandleSendResultResponse(send_result_response) => {
-                // info!("send result response {:?}", send_result_response)
+            Msg::RetrySubmit => {
+                self.state.submit_error = None;
+                self.flush_submissions();
+            }
+            Msg::DiscardSubmit => {
+                self.state.submit_error = None;
+                self.send_result_fetch_task = None;
+                if !self.submission_queue.is_empty() {
+                    self.submission_queue.remove(0);
+                    self.store_submission_queue();
+                }
+                self.flush_submissions();
             }
             Msg::HandleSeedChange(seed) => {
                 self.state.is_started = false;
                 self.state.is_playing = false;
+                self.state.is_paused = false;
+                self.state.is_analysis = false;
                 self.state.step_count = 0;
+                self.state.census.clear();
+                self.is_replay = false;
+
+                // The Daily Challenge is always played on its own settings.
+                if let Some(daily) = DailyChallenge::parse(&seed.label) {
+                    self.state.rule = daily.rule();
+                    self.state.topology = daily.topology();
+                    self.state.edit_budget = Some(daily.edit_budget());
+                } else if DailyChallenge::parse(&self.state.current_seed.label).is_some() {
+                    // Its edit budget stays behind with it.
+                    self.state.edit_budget = None;
+                }
 
                 self.state.current_seed = seed.clone();
+                self.preferences.seed_label = seed.label.clone();
+                self.store_preferences();
                 self.state.game_state.set_cellules(seed.cellules);
+                self.sparse = None;
+                self.generations = None;
+                self.hash_chain = None;
+                self.population_history.clear();
+                self.cell_ages.clear();
+                self.heat_map.clear();
 
                 self.set_active_count();
                 self.history.clear_previous_steps();
@@ -306,6 +453,29 @@ impl Component for App {
                 self.state.is_playing = true;
                 self.state.is_started = true;
                 self.history.clear_previous_steps();
+
+                if self.state.rule.is_larger_than_life() {
+                    self.generations = Some(Box::new(LargerThanLife::from_game_state(
+                        self.state.rule.clone(),
+                        &self.state.game_state,
+                    )));
+                } else if !self.state.rule.is_conway() || self.state.geometry != Geometry::Square {
+                    self.generations = Some(Box::new(GenerationsLife::from_game_state(
+                        self.state.rule.clone(),
+                        self.state.geometry,
+                        &self.state.game_state,
+                    )));
+                } else if self.state.topology == Topology::Infinite {
+                    self.sparse = Some(SparseLife::from_game_state(&self.state.game_state));
+                }
+
+                self.hash_chain = Some(HashChain::new(&self.board_states()));
+                self.population_history
+                    .start(self.population(), self.alive_board());
+                self.cell_ages.clear();
+                self.advance_ages();
+                let (width, board) = self.grid_board();
+                self.heat_map.start(self.grid_origin(), width, &board);
             }
             Msg::StepGame => {
                 if self.state.is_playing {
@@ -313,16 +483,58 @@ impl Component for App {
                     let mut in_endless_loop = false;
 
                     self.state.step_count += 1;
-                    self.state.game_state.step();
 
-                    in_endless_loop = self
-                        .history
-                        .is_in_endless_loop(self.state.game_state.cellules.clone());
+                    if let Some(generations) = self.generations.as_mut() {
+                        generations.step();
+                        in_endless_loop = generations.is_in_endless_loop();
+                    } else if let Some(sparse) = self.sparse.as_mut() {
+                        sparse.step();
+                        in_endless_loop = sparse.is_in_endless_loop();
+                    } else {
+                        self.state.game_state.step();
+                        in_endless_loop = self
+                            .history
+                            .is_in_endless_loop(self.state.game_state.cellules.clone());
+                    }
+
+                    let states = self.board_states();
+                    if let Some(hash_chain) = self.hash_chain.as_mut() {
+                        hash_chain.push(&states);
+                    }
+                    self.record_generation();
+                    self.advance_ages();
+                    let (width, board) = self.grid_board();
+                    self.heat_map.record(self.grid_origin(), width, &board);
+
+                    if in_endless_loop == true || self.state.step_count > MAX_STEP_COUNT {
+                        if let Some(hash_chain) = self.hash_chain.as_mut() {
+                            hash_chain.finish(&states);
+                        }
+
+                        if let Some(generations) = self.generations.as_ref() {
+                            sync_game_state(
+                                &mut self.state.game_state,
+                                &generations.alive_cellules(),
+                            );
+                        }
+                        if let Some(sparse) = self.sparse.as_ref() {
+                            sync_game_state(&mut self.state.game_state, &sparse.alive_cellules());
+                        }
+                        self.state.census = self.final_census();
+
+                        if !self.state.is_analysis {
+                            let end_reason = if states.iter().all(|state| *state == 0) {
+                                EndReason::Extinct
+                            } else if in_endless_loop {
+                                EndReason::EndlessLoop
+                            } else {
+                                EndReason::StepLimit
+                            };
+                            self.record_run(end_reason);
+                        }
 
-                    if in_endless_loop == true || self.state.step_count > 4000 {
-                        // temp
-                        self.state.has_life_high_score = self.check_life_score();
-                        self.state.has_death_high_score = self.check_death_score();
+                        self.state.life_rank = self.projected_rank(Leaderboard::Life);
+                        self.state.death_rank = self.projected_rank(Leaderboard::Death);
 
                         self.state.is_playing = false;
                         self.history.clear_previous_steps();
@@ -333,6 +545,12 @@ impl Component for App {
 
                         return true;
                     }
+
+                    // Replays make the edits of the past run at their generation.
+                    if self.is_replay {
+                        let modifications = self.state.modifications.clone();
+                        self.apply_edits(&modifications, self.state.step_count);
+                    }
                 }
                 self.set_active_count();
             }
@@ -340,9 +558,18 @@ impl Component for App {
                 self.state.is_playing = false;
                 self.history.clear_previous_steps();
             }
+            Msg::TogglePause => {
+                if self.state.is_paused {
+                    self.state.is_paused = false;
+                    self.state.is_playing = true;
+                } else if self.state.is_playing {
+                    self.state.is_paused = true;
+                    self.state.is_playing = false;
+                }
+            }
             Msg::DismissScoreModal => {
-                self.state.has_life_high_score = false;
-                self.state.has_death_high_score = false;
+                self.state.life_rank = None;
+                self.state.death_rank = None;
                 self.update(Msg::HandleSeedChange(self.state.current_seed.clone()));
             }
             Msg::DismissScoreModalClick(event) => {
@@ -351,16 +578,22 @@ impl Component for App {
             }
             Msg::SubmitScore(event) => {
                 event.prevent_default();
-                self.state.has_life_high_score = false;
-                self.state.has_death_high_score = false;
-                // make fetch request
-                self.send_result_fetch_task = Some(self.post_results());
+                self.state.life_rank = None;
+                self.state.death_rank = None;
+                let payload = self.result_payload();
+                self.submission_queue.push(payload);
+                self.store_submission_queue();
+                self.flush_submissions();
 
                 self.update(Msg::HandleSeedChange(self.state.current_seed.clone()));
             }
             Msg::ChangeUserName(user_name) => {
                 self.state.user_name = user_name;
-                self.state.user_name_is_valid = !containsProfanity(&self.state.user_name);
+                self.state.user_name_is_valid = is_valid_user_name(&self.state.user_name);
+                if self.state.user_name_is_valid {
+                    self.preferences.user_name = self.state.user_name.clone();
+                    self.store_preferences();
+                }
             }
             Msg::HandleRender => {
                 let now = js_sys::Date::now();
@@ -377,74 +610,289 @@ impl Component for App {
             }
             Msg::HandleRateChange(rate) => {
                 self.state.rate = rate;
+                self.preferences.rate = rate;
+                self.store_preferences();
+            }
+            Msg::HandleColorModeChange(color_mode) => {
+                self.preferences.color_mode = color_mode;
+                self.store_preferences();
+            }
+            Msg::HandlePaletteChange(palette) => {
+                self.preferences.palette = palette.map(|palette| palette.name.to_owned());
+                self.store_preferences();
+            }
+            Msg::HandleThemeChange(theme) => {
+                self.preferences.theme = theme.map(|theme| theme.name.to_owned());
+                self.store_preferences();
+                self.apply_theme();
+            }
+            Msg::HandleColorSchemeChange => {
+                self.prefers_dark = App::color_scheme_query().is_some_and(|query| query.matches());
+                self.apply_theme();
+            }
+            Msg::HandleTopologyChange(topology) => {
+                // The infinite plane only runs plain Life on the square grid.
+                if topology == Topology::Infinite {
+                    self.state.rule = Rule::conway();
+                    if self.state.geometry != Geometry::Square {
+                        self.state.geometry = Geometry::Square;
+                        self.seed_options = App::seed_options(Geometry::Square);
+                        self.state.current_seed = self.seed_options[0].clone();
+                    }
+                }
+                self.state.topology = topology;
+                self.update(Msg::HandleSeedChange(self.state.current_seed.clone()));
+            }
+            Msg::HandleRuleChange(rule) => {
+                if !rule.is_conway() {
+                    self.state.topology = Topology::Bounded;
+                }
+                self.state.rule = rule;
+                self.update(Msg::HandleSeedChange(self.state.current_seed.clone()));
+            }
+            Msg::HandleGeometryChange(geometry) => {
+                if geometry != Geometry::Square {
+                    self.state.topology = Topology::Bounded;
+                }
+                self.state.geometry = geometry;
+                self.state.rule = geometry.default_rule();
+                self.seed_options = App::seed_options(geometry);
+                self.update(Msg::HandleSeedChange(self.seed_options[0].clone()));
+            }
+            Msg::HandleEditBudgetChange(edit_budget) => {
+                self.state.edit_budget = edit_budget;
+                self.update(Msg::HandleSeedChange(self.state.current_seed.clone()));
+            }
+            Msg::JumpToGeneration(generation) => {
+                let generation = std::cmp::min(generation, i32::MAX as u64);
+                let current_generation = self.state.step_count as u64;
+
+                if generation > current_generation
+                    && self.state.topology == Topology::Bounded
+                    && self.state.rule.is_conway()
+                    && self.state.geometry == Geometry::Square
+                {
+                    let mut engine = HashLife::from_game_state(&self.state.game_state);
+                    engine.step_by(generation - current_generation);
+                    sync_game_state(&mut self.state.game_state, &engine.alive_cellules());
+
+                    // Jumped runs are for analysis only and never qualify for a score.
+                    self.state.step_count = (current_generation + engine.generation()) as i32;
+                    self.state.is_started = true;
+                    self.state.is_playing = false;
+                    self.state.is_paused = false;
+                    self.state.is_analysis = true;
+                    self.hash_chain = None;
+                    self.history.clear_previous_steps();
+                    self.set_active_count();
+                    self.population_history.record_jump(
+                        self.state.step_count,
+                        self.population(),
+                        self.alive_board(),
+                    );
+                    // How long cellules lived through the jump is unknown.
+                    self.cell_ages.clear();
+                    self.advance_ages();
+                    let (width, board) = self.grid_board();
+                    self.heat_map.amend(self.grid_origin(), width, &board);
+                }
             }
             Msg::DismissIntroModalClick => {
-                self.showing_intro_modal = false;
-                self.storage.store(INTRO_MODAL_KEY, Ok("false".to_owned()));
+                self.preferences.showing_intro_modal = false;
+                self.store_preferences();
+            }
+            Msg::ToggleSlotManager => {
+                self.showing_slot_manager = !self.showing_slot_manager;
+            }
+            Msg::SaveSlot(name) => {
+                let slot = self.current_slot(next_slot_id(&self.save_slots), name);
+                self.save_slots.push(slot.clone());
+                store_slot(&mut self.storage, &self.save_slots, &slot);
+            }
+            Msg::LoadSlot(id) => {
+                if let Some(slot) = self.save_slots.iter().find(|slot| slot.id == id).cloned() {
+                    self.load_slot(slot);
+                    self.showing_slot_manager = false;
+                }
+            }
+            Msg::RenameSlot((id, name)) => {
+                if let Some(slot) = self.save_slots.iter_mut().find(|slot| slot.id == id) {
+                    slot.name = name;
+                    let slot = slot.clone();
+                    store_slot(&mut self.storage, &self.save_slots, &slot);
+                }
+            }
+            Msg::DeleteSlot(id) => {
+                self.save_slots.retain(|slot| slot.id != id);
+                remove_slot(&mut self.storage, &self.save_slots, id);
+            }
+            Msg::ExportSlot(id) => {
+                if let Some(slot) = self.save_slots.iter().find(|slot| slot.id == id) {
+                    App::download(&slot.export_file_name(), "application/json", &slot.export());
+                }
+            }
+            Msg::ToggleStats => {
+                self.showing_stats = !self.showing_stats;
+            }
+            Msg::ToggleHeatMap => {
+                self.showing_heat_map = !self.showing_heat_map;
+            }
+            Msg::ExportHeatMapPng => match self.heat_map_png() {
+                Some(href) => App::download_url("heat-map.png", &href),
+                None => warn!("could not draw the heat map"),
+            },
+            Msg::ExportHeatMapCsv => {
+                let (width, height) = self.grid_size();
+                let csv = self.heat_map.to_csv(self.grid_origin(), width, height);
+                App::download("heat-map.csv", "text/csv", &csv);
+            }
+            Msg::ReplayRun(index) => {
+                if let Some(run) = self.past_runs.get(index).cloned() {
+                    self.showing_stats = false;
+                    if self.set_up_run(
+                        &run.seed_label,
+                        run.topology,
+                        run.rule,
+                        run.geometry,
+                        run.edit_budget,
+                        &run.modifications,
+                    ) {
+                        self.update(Msg::Start);
+                        // Replays are watched, not scored again.
+                        self.state.is_analysis = true;
+                        self.is_replay = true;
+                    }
+                }
             }
             Msg::Nope => {}
         }
-        // self.storage.store(GRID_KEY, Json(&self.state.grid));
         true
     }
 
     fn view(&self) -> Html {
-        let error_styles = if self.state.has_no_network == true {
-            ""
-        } else {
-            "display: none;"
-        };
+        let modification_count = self.state.modifications.len() as i32;
 
-        let game_styles = if self.state.has_no_network == true {
-            "display: none;"
+        let (cellules, cellules_width, cellules_height, cellule_neighbors) = self.grid_cellules();
+        let decay_states = self
+            .generations
+            .as_ref()
+            .map(|generations| generations.states().to_vec())
+            .unwrap_or_default();
+        let neighbor_densities = self
+            .generations
+            .as_ref()
+            .map(|generations| generations.neighbor_densities())
+            .unwrap_or_default();
+        let bounding_box = self
+            .sparse
+            .as_ref()
+            .and_then(|sparse| sparse.bounding_box());
+        let ages = match self.preferences.color_mode {
+            ColorMode::Age => {
+                self.cell_ages
+                    .ages_in(self.grid_origin(), cellules_width, cellules_height)
+            }
+            ColorMode::Position => vec![],
+        };
+        let heat = if self.showing_heat_map {
+            self.heat_map
+                .counts_in(self.grid_origin(), cellules_width, cellules_height)
         } else {
-            ""
+            vec![]
         };
 
-        let modification_count = self.state.modifications.len() as i32;
+        let has_life_high_score = self.state.life_rank.is_some();
+        let has_death_high_score = self.state.death_rank.is_some();
 
-        let has_high_score = self.state.has_life_high_score || self.state.has_death_high_score;
+        let has_high_score = has_life_high_score || has_death_high_score;
 
-        let has_only_life_high_score =
-            self.state.has_life_high_score && !self.state.has_death_high_score;
+        let has_only_life_high_score = has_life_high_score && !has_death_high_score;
 
-        let has_only_death_high_score =
-            !self.state.has_life_high_score && self.state.has_death_high_score;
+        let has_only_death_high_score = !has_life_high_score && has_death_high_score;
 
-        let has_both_high_scores =
-            self.state.has_life_high_score && self.state.has_death_high_score;
+        let has_both_high_scores = has_life_high_score && has_death_high_score;
 
         html! {
             <>
-                <div class="game-of-death-wrapper server-down-wrapper" style={error_styles}>
-                    <img src="./clean_up.svg" />
-                    <h1>{"Server is down"}</h1>
-                    <p>{"Give us some time to dive in and clean up the code."}</p>
-                </div>
-                <div class="game-of-death-wrapper"  style={game_styles}>
+                <div class="game-of-death-wrapper">
                     <AppHeader
                         step_count={self.state.step_count}
                         active_count={self.state.active_count}
+                        population={self.population()}
+                        bounding_box={bounding_box}
+                        topology={self.state.topology}
+                        on_topology_change=self.link.callback(|topology| Msg::HandleTopologyChange(topology))
+                        rule={self.state.rule.clone()}
+                        on_rule_change=self.link.callback(|rule| Msg::HandleRuleChange(rule))
+                        geometry={self.state.geometry}
+                        on_geometry_change=self.link.callback(|geometry| Msg::HandleGeometryChange(geometry))
+                        edit_budget={self.state.edit_budget}
+                        on_edit_budget_change=self.link.callback(|edit_budget| Msg::HandleEditBudgetChange(edit_budget))
                         modification_count={modification_count}
                         seed_options={self.seed_options.clone()}
+                        current_seed={self.state.current_seed.clone()}
                         on_seed_change=self.link.callback(|seed| Msg::HandleSeedChange(seed))
+                        rate={self.state.rate}
+                        on_open_slots=self.link.callback(|_| Msg::ToggleSlotManager)
+                        on_open_stats=self.link.callback(|_| Msg::ToggleStats)
                         on_rate_change=self.link.callback(|rate| Msg::HandleRateChange(rate))
+                        color_mode={self.preferences.color_mode}
+                        on_color_mode_change=self.link.callback(Msg::HandleColorModeChange)
+                        palette={self.preferences.palette.as_deref().map(Palette::named)}
+                        on_palette_change=self.link.callback(Msg::HandlePaletteChange)
+                        theme={self.preferences.theme.as_deref().and_then(Theme::named)}
+                        on_theme_change=self.link.callback(Msg::HandleThemeChange)
+                        on_jump=self.link.callback(|generation| Msg::JumpToGeneration(generation))
                         max_fps={self.max_fps}
                     ></AppHeader>
                     <GameGrid
-                        cellules={self.state.game_state.cellules.clone()}
-                        cellules_width={self.state.game_state.cellules_width}
-                        cellules_height={self.state.game_state.cellules_height}
+                        cellules={cellules}
+                        cellules_width={cellules_width}
+                        cellules_height={cellules_height}
                         onclick=self.link.callback(Msg::GridClicked)
-                        cellule_neighbors={self.state.game_state.cellule_neighbors.clone()}
+                        cellule_neighbors={cellule_neighbors}
+                        decay_states={decay_states}
+                        neighbor_densities={neighbor_densities}
+                        state_count={self.state.rule.states}
+                        geometry={self.state.geometry}
+                        ages={ages}
+                        color_mode={self.preferences.color_mode}
+                        palette={self.palette()}
+                        theme={self.theme()}
+                        heat={heat}
+                        showing_heat_map={self.showing_heat_map}
                     ></GameGrid>
+                    <div class="heat-map-controls">
+                        <label class="heat-map-toggle">
+                            <input
+                                type="checkbox"
+                                checked={self.showing_heat_map}
+                                onclick=self.link.callback(|_| Msg::ToggleHeatMap)
+                            />
+                            {"Heat map"}
+                        </label>
+                        <button onclick=self.link.callback(|_| Msg::ExportHeatMapPng)>{"Export PNG"}</button>
+                        <button onclick=self.link.callback(|_| Msg::ExportHeatMapCsv)>{"Export CSV"}</button>
+                    </div>
+                    <PopulationChart
+                        samples={self.population_history.samples().to_vec()}
+                        current_generation={self.state.step_count}
+                    ></PopulationChart>
+
+                    {self.view_connection_status()}
 
                     <div class="hacky-spacer"></div>
 
-                    <div class="start-wrapper" hidden={self.state.has_no_network || self.state.is_started } style={game_styles}>
+                    <div class="start-wrapper" hidden={self.state.is_started}>
                         <button class="start-button" onclick=self.link.callback(|_|  Msg::Start)>{"Start"}</button>
                     </div>
 
+                    <div class="start-wrapper" hidden={!self.state.is_playing && !self.state.is_paused}>
+                        <button class="start-button pause-button" onclick=self.link.callback(|_|  Msg::TogglePause)>
+                            {if self.state.is_paused { "Resume" } else { "Pause" }}
+                        </button>
+                    </div>
+
                     <div class=self.intro_modal_classes() onclick=self.link.callback(|event: MouseEvent|  Msg::DismissIntroModalClick)>
                         <div
                             class="intro-modal"
@@ -515,12 +963,17 @@ impl Component for App {
                                                 <div class="metric-label">{"Active"}</div>
                                                 <div class="metric-value">{self.state.active_count}</div>
                                             </div>
+                                            {self.view_projected_rank("Life Rank", self.state.life_rank)}
+                                            {self.view_projected_rank("Death Rank", self.state.death_rank)}
                                         </div>
+                                        {self.view_census()}
                                         <div class="name-input-wrapper">
                                             <label>{"Enter Name (4 chars max.)"}
                                                 <input
                                                     placeholder="ABCD"
-                                                    class="name-input" oninput=self.link.callback(|event: InputData| Msg::ChangeUserName(event.value))
+                                                    class="name-input"
+                                                    value={self.state.user_name.clone()}
+                                                    oninput=self.link.callback(|event: InputData| Msg::ChangeUserName(event.value))
                                                     maxlength="4"
                                                 />
                                             </label>
@@ -548,6 +1001,7 @@ impl Component for App {
                                     <div>
                                         <h2>{"Bummer! Try Again?"}</h2>
                                         <p>{"You didnt get a Life or a Death score. No worries, it didn't cost you anything."}</p>
+                                        {self.view_census()}
                                         <input
                                             type="button"
                                             class="button try-again-button"
@@ -560,6 +1014,54 @@ impl Component for App {
                         </div>
                     </div>
 
+                    {self.if_then_render(
+                        self.showing_slot_manager,
+                        html!{
+                            <SlotManager
+                                slots={self.save_slots.clone()}
+                                can_save={!self.state.is_started || self.state.is_paused}
+                                on_save=self.link.callback(Msg::SaveSlot)
+                                on_load=self.link.callback(Msg::LoadSlot)
+                                on_rename=self.link.callback(Msg::RenameSlot)
+                                on_delete=self.link.callback(Msg::DeleteSlot)
+                                on_export=self.link.callback(Msg::ExportSlot)
+                                on_close=self.link.callback(|_| Msg::ToggleSlotManager)
+                            ></SlotManager>
+                        }
+                    )}
+
+                    {self.if_then_render(
+                        self.showing_stats,
+                        html!{
+                            <StatsView
+                                runs={self.past_runs.clone()}
+                                on_replay=self.link.callback(Msg::ReplayRun)
+                                on_close=self.link.callback(|_| Msg::ToggleStats)
+                            ></StatsView>
+                        }
+                    )}
+
+                    <div class=self.submit_error_classes()>
+                        <div class="new-score-modal submit-error-modal">
+                            <h2>{"Score Not Sent"}</h2>
+                            <p>{format!("We could not submit your score: {}.", self.state.submit_error.clone().unwrap_or_default())}</p>
+                            <div class="modal-buttons">
+                                <input
+                                    type="button"
+                                    value={"Discard"}
+                                    class="button ignore-button"
+                                    onclick=self.link.callback(|_| Msg::DiscardSubmit)
+                                />
+                                <input
+                                    type="button"
+                                    value={"Retry"}
+                                    class="button submit-button"
+                                    onclick=self.link.callback(|_| Msg::RetrySubmit)
+                                />
+                            </div>
+                        </div>
+                    </div>
+
                 </div>
                 // WTF: why is this not working
                 <FpsDetector oncomplete=self.link.callback(|fps| Msg::HandleFpsDetection(fps))></FpsDetector>
@@ -570,7 +1072,15 @@ impl Component for App {
 
 impl App {
     pub fn modal_classes(&self) -> String {
-        if self.state.step_count > 0 && !self.state.is_playing {
+        if self.state.step_count > 0 && !self.state.is_playing && !self.state.is_analysis {
+            "overlay".to_string()
+        } else {
+            "overlay hidden".to_string()
+        }
+    }
+
+    pub fn submit_error_classes(&self) -> String {
+        if self.state.submit_error.is_some() {
             "overlay".to_string()
         } else {
             "overlay hidden".to_string()
@@ -578,14 +1088,211 @@ impl App {
     }
 
     pub fn intro_modal_classes(&self) -> String {
-        if self.showing_intro_modal {
+        if self.preferences.showing_intro_modal {
             "overlay".to_string()
         } else {
             "overlay hidden".to_string()
         }
     }
 
+    /// What the grid should draw: the board itself, or the part of the
+    /// infinite plane around the pattern when playing on one.
+    fn grid_cellules(&self) -> (Vec<Cellule>, usize, usize, HashMap<usize, Vec<Cellule>>) {
+        if let Some(generations) = self.generations.as_ref() {
+            let width = self.state.game_state.cellules_width;
+            let height = self.state.game_state.cellules_height;
+            let cellules = to_cellules(
+                &self.state.game_state.cellules[0],
+                &generations.alive_cellules(),
+            );
+            // Larger than Life neighborhoods are drawn from their densities.
+            let cellule_neighbors = if self.state.rule.is_larger_than_life() {
+                HashMap::new()
+            } else {
+                neighbor_map(&cellules, width, height)
+            };
+
+            return (cellules, width, height, cellule_neighbors);
+        }
+
+        match &self.sparse {
+            Some(sparse) => {
+                let viewport = sparse.viewport();
+                let cellules = to_cellules(
+                    &self.state.game_state.cellules[0],
+                    &sparse.cellules_in(&viewport),
+                );
+                let cellule_neighbors =
+                    neighbor_map(&cellules, viewport.width(), viewport.height());
+
+                (
+                    cellules,
+                    viewport.width(),
+                    viewport.height(),
+                    cellule_neighbors,
+                )
+            }
+            None => (
+                self.state.game_state.cellules.clone(),
+                self.state.game_state.cellules_width,
+                self.state.game_state.cellules_height,
+                self.state.game_state.cellule_neighbors.clone(),
+            ),
+        }
+    }
+
+    /// The board cellule under a click on the grid. On the infinite plane the
+    /// grid shows the area around the pattern, and only the starting board
+    /// can be edited.
+    fn board_index(&self, column_number: i32, row_number: i32) -> Option<i32> {
+        let width = self.state.game_state.cellules_width as i64;
+        let height = self.state.game_state.cellules_height as i64;
+        let (x, y) = match self.sparse.as_ref() {
+            Some(sparse) => {
+                let viewport = sparse.viewport();
+                (
+                    viewport.min_x + column_number as i64,
+                    viewport.min_y + row_number as i64,
+                )
+            }
+            None => (column_number as i64, row_number as i64),
+        };
+
+        if x >= 0 && y >= 0 && x < width && y < height {
+            Some((y * width + x) as i32)
+        } else {
+            None
+        }
+    }
+
+    /// Applies an edit to whichever engine runs the board. Mid-run edits
+    /// restart loop detection and refresh the active count, the same as the
+    /// verifier does when it replays them.
+    fn toggle_cellule(&mut self, index: usize) {
+        if let Some(generations) = self.generations.as_mut() {
+            generations.toggle_cellule(index);
+        } else if let Some(sparse) = self.sparse.as_mut() {
+            sparse.toggle_cellule(index);
+        } else {
+            self.state.game_state.toggle_cellule(index);
+            if self.state.is_started {
+                self.history.clear_previous_steps();
+            }
+        }
+
+        if self.state.is_started {
+            self.set_active_count();
+            self.population_history
+                .amend(self.population(), self.alive_board());
+            let (width, board) = self.grid_board();
+            self.cell_ages.amend(self.grid_origin(), width, &board);
+            self.heat_map.amend(self.grid_origin(), width, &board);
+        }
+    }
+
+    /// Alive flags for the board, or the starting board's window of the
+    /// infinite plane.
+    fn alive_board(&self) -> Vec<bool> {
+        if let Some(generations) = self.generations.as_ref() {
+            generations.alive_cellules()
+        } else if let Some(sparse) = self.sparse.as_ref() {
+            sparse.alive_cellules()
+        } else {
+            Engine::alive_cellules(&self.state.game_state)
+        }
+    }
+
+    /// The objects on the final board. Only plain Life on square cellules
+    /// has the objects the census knows about.
+    fn final_census(&self) -> Vec<CensusEntry> {
+        if !self.state.rule.is_conway() || self.state.geometry != Geometry::Square {
+            return vec![];
+        }
+
+        take_census(
+            self.state.game_state.cellules_width,
+            self.state.game_state.cellules_height,
+            &Engine::alive_cellules(&self.state.game_state),
+        )
+    }
+
+    fn population(&self) -> usize {
+        if let Some(generations) = self.generations.as_ref() {
+            generations.population()
+        } else if let Some(sparse) = self.sparse.as_ref() {
+            sparse.population()
+        } else {
+            Engine::population(&self.state.game_state)
+        }
+    }
+
+    /// Adds the generation on the grid to the population chart. The sparse
+    /// engine counts births and deaths itself, since they can happen off the
+    /// starting board.
+    fn record_generation(&mut self) {
+        let changes = self.sparse.as_ref().map(SparseLife::last_changes);
+        self.population_history.record(
+            self.state.step_count,
+            self.population(),
+            self.alive_board(),
+            changes,
+        );
+    }
+
+    /// Makes the cellules on the grid a generation older.
+    fn advance_ages(&mut self) {
+        let (width, board) = self.grid_board();
+        self.cell_ages.advance(self.grid_origin(), width, &board);
+    }
+
+    /// How many cellules wide and high the grid is.
+    fn grid_size(&self) -> (usize, usize) {
+        match self.sparse.as_ref() {
+            Some(sparse) => {
+                let viewport = sparse.viewport();
+                (viewport.width(), viewport.height())
+            }
+            None => (
+                self.state.game_state.cellules_width,
+                self.state.game_state.cellules_height,
+            ),
+        }
+    }
+
+    /// Where the first cellule on the grid is on the plane.
+    fn grid_origin(&self) -> (i64, i64) {
+        self.sparse
+            .as_ref()
+            .map(|sparse| {
+                let viewport = sparse.viewport();
+                (viewport.min_x, viewport.min_y)
+            })
+            .unwrap_or((0, 0))
+    }
+
+    /// Alive flags for the cellules on the grid, and how wide it is.
+    fn grid_board(&self) -> (usize, Vec<bool>) {
+        match self.sparse.as_ref() {
+            Some(sparse) => {
+                let viewport = sparse.viewport();
+                (viewport.width(), sparse.cellules_in(&viewport))
+            }
+            None => (self.state.game_state.cellules_width, self.alive_board()),
+        }
+    }
+
     fn set_active_count(&mut self) -> () {
+        if let Some(generations) = self.generations.as_ref() {
+            self.state.active_count = generations.population() as i32;
+            return;
+        }
+        // The score counts the cellules on the board that is submitted, so
+        // on the infinite plane the ones that left it do not count.
+        if let Some(sparse) = self.sparse.as_ref() {
+            self.state.active_count = sparse.population_on_board() as i32;
+            return;
+        }
+
         let mut active_count = 0;
         for cellule in self.state.game_state.cellules.iter() {
             if cellule.life_state == LifeState::Alive {
@@ -595,57 +1302,92 @@ impl App {
         self.state.active_count = active_count;
     }
 
-    fn fetch_scores(&mut self) -> yew::services::fetch::FetchTask {
-        let callback = self.link.callback(
-            move |response: Response<Json<Result<GetScoresResponseData, Error>>>| {
-                let (meta, Json(data)) = response.into_parts();
-                if meta.status.is_success() {
-                    Msg::HandleGetScoresResponse(data)
-                } else {
-                    warn!("HTTP request failure");
-                    Msg::HandleGetScoresError
-                    // Msg::Nope
-                }
-            },
-        );
+    fn fetch_scores(&mut self) {
+        let callback = self.link.callback(Msg::HandleGetScoresResponse);
+        self.fetch_scores_fetch_task = Some(self.api.get_high_scores(callback));
+    }
 
-        let submit_result_url = self.env_vars.API_URL_GET_HIGH_SCORES.clone();
-        let request = Request::get(submit_result_url)
-            .header("Content-Type", "application/json")
-            .body(Nothing)
-            .unwrap();
-        FetchService::new().fetch(request, callback).unwrap()
+    /// Sends the oldest queued score. Scores leave the queue only once the
+    /// server has accepted them, so nothing earned offline is lost.
+    fn flush_submissions(&mut self) {
+        if self.send_result_fetch_task.is_some() || self.state.submit_error.is_some() {
+            return;
+        }
+
+        if let Some(payload) = self.submission_queue.first() {
+            let callback = self.link.callback(Msg::HandleSendResultResponse);
+            self.send_result_fetch_task = Some(self.api.submit_result(payload, callback));
+        }
     }
 
-    fn post_results(&mut self) -> yew::services::fetch::FetchTask {
-        let callback = self.link.callback(
-            move |response: Response<Json<Result<ResultResponseData, Error>>>| {
-                let (meta, Json(data)) = response.into_parts();
-                if meta.status.is_success() {
-                    Msg::HandleSendResultResponse(data)
-                } else {
-                    warn!("HTTP request failure");
-                    Msg::Nope
-                }
-            },
+    fn store_preferences(&mut self) {
+        self.preferences.store(&mut self.storage);
+    }
+
+    fn store_submission_queue(&mut self) {
+        self.storage.store(
+            SUBMISSION_QUEUE_KEY,
+            &SUBMISSION_QUEUE_SCHEMA,
+            &self.submission_queue,
         );
+    }
 
-        let serialized_cellules = self
-            .state
-            .game_state
-            .cellules
-            .iter()
-            .map(|cellule| {
-                if cellule.life_state == LifeState::Dead {
-                    "0".to_string()
-                } else {
-                    "1".to_string()
-                }
-            })
-            .collect::<Vec<String>>()
-            .join("");
+    /// While offline the scores endpoint is polled so queued scores go out
+    /// as soon as the server can be reached again.
+    fn set_online(&mut self, is_online: bool) {
+        self.state.has_no_network = !is_online;
+
+        if is_online {
+            self.connection_check = None;
+        } else if self.connection_check.is_none() {
+            let callback = self.link.callback(|_| Msg::CheckConnection);
+            self.connection_check =
+                Some(IntervalService::new().spawn(CONNECTION_CHECK_INTERVAL, callback));
+        }
+    }
 
-        let raw_payload = SendResultPayload {
+    fn view_connection_status(&self) -> Html {
+        let pending_count = self.submission_queue.len();
+
+        html! {
+            <div class="connection-status">
+                {self.if_then_render(
+                    self.state.has_no_network,
+                    html!{ <span class="offline-notice">{"Offline: using the last saved high scores"}</span> }
+                )}
+                {self.if_then_render(
+                    pending_count > 0,
+                    html!{
+                        <span class="pending-submissions">
+                            {format!("{} score{} waiting to be sent", pending_count, if pending_count == 1 { "" } else { "s" })}
+                        </span>
+                    }
+                )}
+            </div>
+        }
+    }
+
+    /// Every cellule's state on the board, as it is hashed and submitted.
+    fn board_states(&self) -> Vec<u8> {
+        if let Some(generations) = self.generations.as_ref() {
+            return generations.states().to_vec();
+        }
+
+        let alive_cellules = match self.sparse.as_ref() {
+            Some(sparse) => sparse.alive_cellules(),
+            None => Engine::alive_cellules(&self.state.game_state),
+        };
+        alive_cellules
+            .into_iter()
+            .map(|alive| alive as u8)
+            .collect()
+    }
+
+    fn result_payload(&self) -> SendResultPayload {
+        let serialized_cellules = serialize_states(&self.board_states());
+
+        SendResultPayload {
+            schema_version: SCHEMA_VERSION,
             modifications: self.state.modifications.clone(),
             step_count: self.state.step_count,
             active_count: self.state.active_count,
@@ -657,16 +1399,16 @@ impl App {
                 active: false,
             },
             user_name: self.state.user_name.clone(),
-        };
-
-        let payload = Json(&raw_payload);
-
-        let submit_result_url = self.env_vars.API_URL_SUBMIT_RESULT.clone();
-        let request = Request::post(submit_result_url)
-            .header("Content-Type", "application/json")
-            .body(payload)
-            .unwrap();
-        FetchService::new().fetch(request, callback).unwrap()
+            topology: self.state.topology,
+            rule: self.state.rule.clone(),
+            geometry: self.state.geometry,
+            checkpoints: self
+                .hash_chain
+                .as_ref()
+                .map(|hash_chain| hash_chain.checkpoints().to_vec())
+                .unwrap_or_default(),
+            edit_budget: self.state.edit_budget,
+        }
     }
 
     fn get_env_vars() -> EnvVars {
@@ -699,96 +1441,319 @@ impl App {
         env_vars
     }
 
+    /// The seeds for `geometry`, with today's Daily Challenge where it applies.
+    fn seed_options(geometry: Geometry) -> Vec<Seed> {
+        let mut seeds = geometry.seeds();
+
+        let now = js_sys::Date::new_0();
+        let today = DailyChallenge::new(
+            now.get_utc_full_year() as i32,
+            now.get_utc_month() + 1,
+            now.get_utc_date(),
+        );
+        if let Some(daily) = today.filter(|daily| daily.geometry() == geometry) {
+            seeds.push(daily.seed());
+        }
+
+        seeds
+    }
+
+    fn is_out_of_edits(&self) -> bool {
+        self.state
+            .edit_budget
+            .is_some_and(|edit_budget| self.state.modifications.len() >= edit_budget)
+    }
+
     fn render_next_frame(&mut self) {
         let render_frame = self.link.callback(|_| Msg::HandleRender);
         let handle = RenderService::new().request_animation_frame(render_frame);
         self.render_loop = Some(Box::new(handle));
     }
 
-    fn check_life_score(&mut self) -> bool {
-        let mut sorted_seed_scores = self
+    /// Where this run would place on a leaderboard, if it makes the list.
+    fn projected_rank(&self, leaderboard: Leaderboard) -> Option<usize> {
+        let payload = self.result_payload();
+        let key = LeaderboardKey::of(&payload);
+        let scores = self
             .previous_scores
             .iter()
-            .cloned()
-            .filter(|score| {
-                score.seed_label == self.state.current_seed.label
-                    && score.modifications.len() == self.state.modifications.len()
-            })
-            .collect::<Vec<GetScoresResponseDataItem>>();
+            .filter(|score| LeaderboardKey::of(&score.payload) == key)
+            .map(|score| Score::of(&score.payload))
+            .collect::<Vec<Score>>();
 
-        sorted_seed_scores.sort_by(|score_a, score_b| {
-            score_a.step_count.partial_cmp(&score_b.step_count).unwrap()
-        });
+        Ranking::new(leaderboard).projected_rank(&scores, &Score::of(&payload))
+    }
 
-        if sorted_seed_scores.len() > 0 {
-            let length = std::cmp::min(sorted_seed_scores.len() - 1, 20);
+    fn view_projected_rank(&self, label: &str, rank: Option<usize>) -> Html {
+        match rank {
+            Some(rank) => html! {
+                <div class="metric">
+                    <div class="metric-label">{label}</div>
+                    <div class="metric-value">{format!("#{}", rank)}</div>
+                </div>
+            },
+            None => html! {},
+        }
+    }
 
-            let (top_scores, _) = sorted_seed_scores.split_at(length);
+    fn view_census(&self) -> Html {
+        if self.state.census.is_empty() {
+            return html! {};
+        }
 
-            let lowest_score = top_scores.last();
+        html! {
+            <div class="census">
+                <div class="metric-label">{"Left on the Board"}</div>
+                <ul class="census-list">
+                    {self.state.census.iter().map(|entry| html! {
+                        <li class="census-entry">
+                            <span class="census-count">{entry.count}</span>
+                            <span>{&entry.name}</span>
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+            </div>
+        }
+    }
 
-            if lowest_score.is_some() {
-                // need to check active count for tie breakers
-                lowest_score.unwrap().step_count < self.state.step_count
-            } else {
-                true
-            }
+    fn if_then_render(&self, condition: bool, snippet: VNode) -> VNode {
+        if condition {
+            snippet
         } else {
-            true
+            html! {}
         }
     }
 
-    fn check_death_score(&mut self) -> bool {
-        let mut sorted_seed_scores = self
-            .previous_scores
-            .iter()
-            .cloned()
-            .filter(|score| {
-                score.seed_label == self.state.current_seed.label
-                    && score.modifications.len() == self.state.modifications.len()
-            })
-            .collect::<Vec<GetScoresResponseDataItem>>();
+    fn current_slot(&self, id: u32, name: String) -> SaveSlot {
+        SaveSlot {
+            version: SLOT_VERSION,
+            id,
+            name,
+            saved_at: js_sys::Date::now(),
+            seed_label: self.state.current_seed.label.clone(),
+            modifications: self.state.modifications.clone(),
+            cellules_width: self.state.game_state.cellules_width,
+            cellules_height: self.state.game_state.cellules_height,
+            topology: self.state.topology,
+            rule: self.state.rule.clone(),
+            geometry: self.state.geometry,
+            edit_budget: self.state.edit_budget,
+            generation: self.state.step_count,
+        }
+    }
 
-        sorted_seed_scores.sort_by(|score_a, score_b| {
-            score_b
-                .active_count
-                .partial_cmp(&score_a.active_count)
-                .unwrap()
-        });
+    /// Rebuilds a saved run: the seed and settings, the edits made before the
+    /// start, then the run replayed to the saved generation with its mid-run
+    /// edits, paused there.
+    fn load_slot(&mut self, slot: SaveSlot) {
+        if slot.cellules_width != self.state.game_state.cellules_width
+            || slot.cellules_height != self.state.game_state.cellules_height
+        {
+            warn!("save slot {} was saved on another board size", slot.id);
+            return;
+        }
+        if !self.set_up_run(
+            &slot.seed_label,
+            slot.topology,
+            slot.rule.clone(),
+            slot.geometry,
+            slot.edit_budget,
+            &slot.modifications,
+        ) {
+            return;
+        }
 
-        if sorted_seed_scores.len() > 0 {
-            let length = std::cmp::min(sorted_seed_scores.len() - 1, 20);
+        if slot.generation > 0 {
+            // The run was recorded when it was played, so ending it again
+            // while it is replayed neither records nor scores it.
+            self.state.is_analysis = true;
+            self.update(Msg::Start);
+            while self.state.is_playing && self.state.step_count < slot.generation {
+                self.update(Msg::StepGame);
+                if self.state.is_playing {
+                    self.apply_edits(&slot.modifications, self.state.step_count);
+                }
+            }
+            // A run still going is the player's to finish and score.
+            self.state.is_analysis = !self.state.is_playing;
+            self.update(Msg::TogglePause);
+        }
 
-            let (top_scores, _) = sorted_seed_scores.split_at(length);
+        self.set_active_count();
+    }
 
-            let highest_score = top_scores.last();
+    /// Puts a saved or past run's seed and settings on the board, with the
+    /// edits it made before starting. `false` if the seed no longer exists.
+    fn set_up_run(
+        &mut self,
+        seed_label: &str,
+        topology: Topology,
+        rule: Rule,
+        geometry: Geometry,
+        edit_budget: Option<usize>,
+        modifications: &[GridModification],
+    ) -> bool {
+        let seed = match geometry.seed(seed_label) {
+            Some(seed) => seed,
+            None => {
+                warn!("unknown seed: {}", seed_label);
+                return false;
+            }
+        };
 
-            if highest_score.is_some() {
-                // need to check active count for tie breakers
-                let is_death_score = highest_score.unwrap().active_count > self.state.active_count;
-                is_death_score
-            } else {
-                true
+        self.state.topology = topology;
+        self.state.rule = rule;
+        self.state.geometry = geometry;
+        self.seed_options = App::seed_options(geometry);
+        self.update(Msg::HandleSeedChange(seed));
+        // After the seed change, which drops the Daily Challenge's budget.
+        self.state.edit_budget = edit_budget;
+        // Set up front so a run that ends while it is replayed is scored with them.
+        self.state.modifications = modifications.to_vec();
+
+        self.apply_edits(modifications, 0);
+        true
+    }
+
+    fn apply_edits(&mut self, modifications: &[GridModification], step_index: i32) {
+        for modification in modifications.iter() {
+            if modification.step_index == step_index {
+                self.toggle_cellule(modification.grid_index as usize);
             }
-        } else {
-            true
         }
     }
 
-    fn if_then_render(&self, condition: bool, snippet: VNode) -> VNode {
-        if condition {
-            snippet
+    fn record_run(&mut self, end_reason: EndReason) {
+        let run = PastRun {
+            finished_at: js_sys::Date::now(),
+            seed_label: self.state.current_seed.label.clone(),
+            topology: self.state.topology,
+            rule: self.state.rule.clone(),
+            geometry: self.state.geometry,
+            edit_budget: self.state.edit_budget,
+            step_count: self.state.step_count,
+            active_count: self.state.active_count,
+            end_reason,
+            modifications: self.state.modifications.clone(),
+            census: self.state.census.clone(),
+        };
+        record_run(&mut self.storage, &mut self.past_runs, run);
+    }
+
+    fn theme(&self) -> Theme {
+        Theme::resolve(self.preferences.theme.as_deref(), self.prefers_dark)
+    }
+
+    /// The palette the player picked, or else the theme's.
+    fn palette(&self) -> Palette {
+        match self.preferences.palette.as_deref() {
+            Some(name) => Palette::named(name),
+            None => self.theme().palette,
+        }
+    }
+
+    /// Sets the theme's colors as CSS variables on the page.
+    fn apply_theme(&self) {
+        let root = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.document_element())
+            .and_then(|element| element.dyn_into::<web_sys::HtmlElement>().ok());
+
+        match root {
+            Some(root) => {
+                for (name, value) in self.theme().css_variables() {
+                    let _ = root.style().set_property(name, &value);
+                }
+            }
+            None => warn!("could not apply the theme"),
+        }
+    }
+
+    fn color_scheme_query() -> Option<web_sys::MediaQueryList> {
+        web_sys::window()?
+            .match_media("(prefers-color-scheme: dark)")
+            .ok()?
+    }
+
+    /// Calls `callback` whenever the system switches between light and dark.
+    fn listen_to_color_scheme(
+        callback: Callback<()>,
+    ) -> Option<Closure<dyn FnMut(web_sys::Event)>> {
+        let query = App::color_scheme_query()?;
+        let listener =
+            Closure::wrap(Box::new(move |_: web_sys::Event| callback.emit(()))
+                as Box<dyn FnMut(web_sys::Event)>);
+        query
+            .add_event_listener_with_callback("change", listener.as_ref().unchecked_ref())
+            .ok()?;
+
+        Some(listener)
+    }
+
+    /// The heat map of the grid drawn as a PNG, as a data URL.
+    fn heat_map_png(&self) -> Option<String> {
+        let (width, height) = self.grid_size();
+        let counts = self.heat_map.counts_in(self.grid_origin(), width, height);
+        let busiest = counts.iter().copied().max().unwrap_or(0).max(1);
+        let heat_gradient = HEAT_RAMP.gradient();
+
+        let canvas_element = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.create_element("canvas").ok())
+            .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())?;
+        canvas_element.set_width((width * HEAT_MAP_PNG_SCALE) as u32);
+        canvas_element.set_height((height * HEAT_MAP_PNG_SCALE) as u32);
+        let ctx = canvas_element
+            .get_context("2d")
+            .ok()??
+            .dyn_into::<CanvasRenderingContext2d>()
+            .ok()?;
+
+        for (index, count) in counts.into_iter().enumerate() {
+            let color = heat_gradient.interpolate_colors(count as f32 / busiest as f32);
+            let fill = format!("rgb({}, {}, {})", color.red, color.green, color.blue);
+            ctx.set_fill_style(&JsValue::from_str(&fill));
+            ctx.fill_rect(
+                ((index % width) * HEAT_MAP_PNG_SCALE) as f64,
+                ((index / width) * HEAT_MAP_PNG_SCALE) as f64,
+                HEAT_MAP_PNG_SCALE as f64,
+                HEAT_MAP_PNG_SCALE as f64,
+            );
+        }
+
+        canvas_element.to_data_url().ok()
+    }
+
+    /// Hands `contents` to the browser as a file download.
+    fn download(file_name: &str, media_type: &str, contents: &str) {
+        let href = format!(
+            "data:{};charset=utf-8,{}",
+            media_type,
+            String::from(js_sys::encode_uri_component(contents))
+        );
+        App::download_url(file_name, &href);
+    }
+
+    fn download_url(file_name: &str, href: &str) {
+        let document = web_sys::window().and_then(|window| window.document());
+        let link = document
+            .and_then(|document| document.create_element("a").ok())
+            .and_then(|element| element.dyn_into::<web_sys::HtmlElement>().ok());
+
+        if let Some(link) = link {
+            let _ = link.set_attribute("href", href);
+            let _ = link.set_attribute("download", file_name);
+            link.click();
         } else {
-            html! {}
+            warn!("could not export {}", file_name);
         }
     }
 }
 
-impl State {
-    #[allow(dead_code)]
-    fn total(&self) -> usize {
-        self.grid.len()
-    }
+/// Whether a name can go on the leaderboards, whether it was just typed or
+/// remembered from last time.
+fn is_valid_user_name(user_name: &str) -> bool {
+    !user_name.is_empty() && !containsProfanity(user_name)
 }
 
 #[wasm_bindgen]
@@ -0,0 +1,455 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use yew::callback::Callback;
+use yew::format::{Nothing, Text};
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+
+use cellule_life_schema::{GetScoresResponseData, ResultResponseData, SendResultPayload};
+
+use super::EnvVars;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Why a request to the scores API failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiError {
+    /// The request never got an answer: offline, DNS, CORS or a bad URL.
+    Network(String),
+    /// No answer within `REQUEST_TIMEOUT`.
+    Timeout,
+    /// The server refused the request (4xx).
+    Client(u16, String),
+    /// The server failed to handle the request (5xx).
+    Server(u16),
+    /// The server answered, but not with the JSON we expected.
+    Decode(String),
+}
+
+impl ApiError {
+    /// Failures that may go away on their own are retried; a request the
+    /// server refused or answered in the wrong shape will fail the same way
+    /// again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::Network(_) | ApiError::Timeout | ApiError::Server(_) => true,
+            ApiError::Client(status, _) => *status == 408 || *status == 429,
+            ApiError::Decode(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::Network(reason) => {
+                write!(formatter, "could not reach the server ({})", reason)
+            }
+            ApiError::Timeout => write!(formatter, "the server took too long to answer"),
+            ApiError::Client(status, body) if body.is_empty() => {
+                write!(formatter, "the server refused the request ({})", status)
+            }
+            ApiError::Client(status, body) => {
+                write!(
+                    formatter,
+                    "the server refused the request ({}: {})",
+                    status, body
+                )
+            }
+            ApiError::Server(status) => write!(formatter, "the server had a problem ({})", status),
+            ApiError::Decode(reason) => {
+                write!(
+                    formatter,
+                    "the server sent an unexpected answer ({})",
+                    reason
+                )
+            }
+        }
+    }
+}
+
+/// Typed access to the scores API. Every call retries with exponential
+/// backoff and reports its final outcome through the callback exactly once.
+pub struct ApiClient {
+    get_high_scores_url: String,
+    submit_result_url: String,
+}
+
+impl ApiClient {
+    pub fn new(env_vars: EnvVars) -> ApiClient {
+        ApiClient {
+            get_high_scores_url: env_vars.API_URL_GET_HIGH_SCORES,
+            submit_result_url: env_vars.API_URL_SUBMIT_RESULT,
+        }
+    }
+
+    pub fn get_high_scores(
+        &self,
+        callback: Callback<Result<GetScoresResponseData, ApiError>>,
+    ) -> ApiTask {
+        ApiTask::start(
+            ApiRequest {
+                url: self.get_high_scores_url.clone(),
+                body: None,
+            },
+            callback,
+        )
+    }
+
+    pub fn submit_result(
+        &self,
+        payload: &SendResultPayload,
+        callback: Callback<Result<ResultResponseData, ApiError>>,
+    ) -> ApiTask {
+        ApiTask::start(
+            ApiRequest {
+                url: self.submit_result_url.clone(),
+                body: Some(payload.encode()),
+            },
+            callback,
+        )
+    }
+}
+
+/// A response body the client knows how to decode.
+pub trait ResponseBody: Sized + 'static {
+    fn decode(body: &str) -> Result<Self, ApiError>;
+}
+
+impl ResponseBody for GetScoresResponseData {
+    /// Older scores in the response are upgraded to the current schema and
+    /// newer ones are left out.
+    fn decode(body: &str) -> Result<Self, ApiError> {
+        GetScoresResponseData::decode(body).map_err(|error| ApiError::Decode(error.to_string()))
+    }
+}
+
+impl ResponseBody for ResultResponseData {
+    fn decode(body: &str) -> Result<Self, ApiError> {
+        serde_json::from_str(body).map_err(|error| ApiError::Decode(error.to_string()))
+    }
+}
+
+/// A request in flight, including any retry waiting to go out. Dropping it
+/// cancels the request and no callback is called.
+pub struct ApiTask {
+    tasks: Rc<RefCell<AttemptTasks>>,
+    cancelled: Rc<Cell<bool>>,
+}
+
+#[derive(Default)]
+struct AttemptTasks {
+    fetch: Option<FetchTask>,
+    timeout: Option<TimeoutTask>,
+    retry: Option<TimeoutTask>,
+}
+
+/// What the callbacks of an attempt keep of their `ApiTask`. The tasks they
+/// live in are only borrowed weakly, so that dropping the `ApiTask` drops
+/// them, and a callback already running sees `cancelled` and stops there.
+#[derive(Clone)]
+struct AttemptHandle {
+    tasks: Weak<RefCell<AttemptTasks>>,
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl AttemptHandle {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+
+    fn update_tasks(&self, update: impl FnOnce(&mut AttemptTasks)) {
+        if let Some(tasks) = self.tasks.upgrade() {
+            update(&mut tasks.borrow_mut());
+        }
+    }
+}
+
+/// Enough to send the same request again: a GET when there is no body,
+/// otherwise a POST of the JSON body.
+#[derive(Clone)]
+struct ApiRequest {
+    url: String,
+    body: Option<String>,
+}
+
+impl ApiRequest {
+    fn build(&self) -> Result<Request<Text>, ApiError> {
+        let request = match &self.body {
+            Some(body) => Request::post(self.url.as_str())
+                .header("Content-Type", "application/json")
+                .body(Ok(body.clone())),
+            None => Request::get(self.url.as_str())
+                .header("Content-Type", "application/json")
+                .body(Nothing.into()),
+        };
+
+        request.map_err(|error| ApiError::Network(error.to_string()))
+    }
+}
+
+impl ApiTask {
+    fn new() -> ApiTask {
+        ApiTask {
+            tasks: Rc::new(RefCell::new(AttemptTasks::default())),
+            cancelled: Rc::new(Cell::new(false)),
+        }
+    }
+
+    fn start<T: ResponseBody>(
+        request: ApiRequest,
+        callback: Callback<Result<T, ApiError>>,
+    ) -> ApiTask {
+        let task = ApiTask::new();
+        send(request, 1, task.handle(), callback);
+
+        task
+    }
+
+    fn handle(&self) -> AttemptHandle {
+        AttemptHandle {
+            tasks: Rc::downgrade(&self.tasks),
+            cancelled: self.cancelled.clone(),
+        }
+    }
+}
+
+impl Drop for ApiTask {
+    fn drop(&mut self) {
+        self.cancelled.set(true);
+    }
+}
+
+fn send<T: ResponseBody>(
+    request: ApiRequest,
+    attempt: u32,
+    handle: AttemptHandle,
+    callback: Callback<Result<T, ApiError>>,
+) {
+    if handle.is_cancelled() {
+        return;
+    }
+
+    // The response and the timeout race; whichever comes first settles the attempt.
+    let settled = Rc::new(Cell::new(false));
+
+    let on_response = {
+        let (request, handle, callback, settled) = (
+            request.clone(),
+            handle.clone(),
+            callback.clone(),
+            settled.clone(),
+        );
+        Callback::from(move |response: Response<Text>| {
+            if !handle.is_cancelled() && !settled.replace(true) {
+                handle.update_tasks(|tasks| tasks.timeout = None);
+                let (meta, body) = response.into_parts();
+                let result =
+                    check_status(meta.status.as_u16(), body).and_then(|body| T::decode(&body));
+                settle(
+                    result,
+                    request.clone(),
+                    attempt,
+                    handle.clone(),
+                    callback.clone(),
+                );
+            }
+        })
+    };
+
+    let on_timeout = {
+        let (request, handle, callback, settled) =
+            (request.clone(), handle.clone(), callback.clone(), settled);
+        Callback::from(move |_| {
+            if !handle.is_cancelled() && !settled.replace(true) {
+                // Dropping the fetch task aborts the request.
+                handle.update_tasks(|tasks| tasks.fetch = None);
+                settle(
+                    Err(ApiError::Timeout),
+                    request.clone(),
+                    attempt,
+                    handle.clone(),
+                    callback.clone(),
+                );
+            }
+        })
+    };
+
+    let fetch = request.build().and_then(|built| {
+        FetchService::new()
+            .fetch(built, on_response)
+            .map_err(|error| ApiError::Network(error.to_string()))
+    });
+
+    match fetch {
+        Ok(fetch) => {
+            let timeout = TimeoutService::new().spawn(REQUEST_TIMEOUT, on_timeout);
+            handle.update_tasks(|tasks| {
+                tasks.fetch = Some(fetch);
+                tasks.timeout = Some(timeout);
+            });
+        }
+        Err(error) => settle(Err(error), request, attempt, handle, callback),
+    }
+}
+
+fn settle<T: ResponseBody>(
+    result: Result<T, ApiError>,
+    request: ApiRequest,
+    attempt: u32,
+    handle: AttemptHandle,
+    callback: Callback<Result<T, ApiError>>,
+) {
+    if handle.is_cancelled() {
+        return;
+    }
+
+    let delay = match &result {
+        Err(error) if error.is_retryable() => retry_delay(attempt),
+        _ => None,
+    };
+
+    match delay {
+        Some(delay) => {
+            warn!(
+                "attempt {} failed: {:?}, retrying in {:?}",
+                attempt,
+                result.err(),
+                delay
+            );
+            let retry = {
+                let handle = handle.clone();
+                Callback::from(move |_| {
+                    send(
+                        request.clone(),
+                        attempt + 1,
+                        handle.clone(),
+                        callback.clone(),
+                    )
+                })
+            };
+            let retry = TimeoutService::new().spawn(delay, retry);
+            handle.update_tasks(|tasks| tasks.retry = Some(retry));
+        }
+        None => callback.emit(result),
+    }
+}
+
+/// How long to wait before the attempt after `attempt`, doubling each time.
+/// `None` once the attempts are used up.
+fn retry_delay(attempt: u32) -> Option<Duration> {
+    if attempt >= MAX_ATTEMPTS {
+        None
+    } else {
+        Some(FIRST_RETRY_DELAY * 2u32.pow(attempt - 1))
+    }
+}
+
+/// Sorts a raw response into a body to decode or the matching error. Fetch
+/// reports requests that never reached the server with status 0.
+fn check_status(status: u16, body: Text) -> Result<String, ApiError> {
+    match status {
+        200..=299 => body.map_err(|error| ApiError::Decode(error.to_string())),
+        400..=499 => Err(ApiError::Client(status, body.unwrap_or_default())),
+        500..=599 => Err(ApiError::Server(status)),
+        _ => Err(ApiError::Network(match body {
+            Err(error) => error.to_string(),
+            Ok(_) => format!("unexpected status {}", status),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_check_status() {
+        assert_eq!(check_status(200, Ok("{}".to_owned())), Ok("{}".to_owned()));
+        assert_eq!(
+            check_status(422, Ok("bad seed".to_owned())),
+            Err(ApiError::Client(422, "bad seed".to_owned()))
+        );
+        assert_eq!(
+            check_status(503, Ok("".to_owned())),
+            Err(ApiError::Server(503))
+        );
+        assert!(matches!(
+            check_status(0, Err(anyhow!("failed to fetch"))),
+            Err(ApiError::Network(_))
+        ));
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        assert!(ApiError::Network("offline".to_owned()).is_retryable());
+        assert!(ApiError::Timeout.is_retryable());
+        assert!(ApiError::Server(502).is_retryable());
+        assert!(ApiError::Client(429, "".to_owned()).is_retryable());
+        assert!(!ApiError::Client(400, "".to_owned()).is_retryable());
+        assert!(!ApiError::Decode("eof".to_owned()).is_retryable());
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Some(Duration::from_millis(500)));
+        assert_eq!(retry_delay(2), Some(Duration::from_millis(1000)));
+        assert_eq!(retry_delay(3), Some(Duration::from_millis(2000)));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let scores = GetScoresResponseData::decode(r#"{"scores": []}"#);
+        assert!(scores.unwrap().scores.is_empty());
+
+        let scores = <GetScoresResponseData as ResponseBody>::decode("<html>");
+        assert!(matches!(scores, Err(ApiError::Decode(_))));
+
+        let message = ResultResponseData::decode(r#"{"message": "stored"}"#);
+        assert_eq!(message.unwrap().message, "stored");
+    }
+
+    #[test]
+    fn test_dropped_task_never_calls_back() {
+        let emitted = Rc::new(Cell::new(0));
+        let callback = {
+            let emitted = emitted.clone();
+            Callback::from(move |_: Result<ResultResponseData, ApiError>| {
+                emitted.set(emitted.get() + 1)
+            })
+        };
+        let request = ApiRequest {
+            url: "https://example.com/results".to_owned(),
+            body: None,
+        };
+        let stored = || {
+            Ok(ResultResponseData {
+                message: "stored".to_owned(),
+            })
+        };
+
+        let task = ApiTask::new();
+        settle(
+            stored(),
+            request.clone(),
+            1,
+            task.handle(),
+            callback.clone(),
+        );
+        assert_eq!(emitted.get(), 1);
+
+        let handle = task.handle();
+        drop(task);
+        assert!(handle.tasks.upgrade().is_none());
+        settle(stored(), request, 1, handle, callback);
+        assert_eq!(emitted.get(), 1);
+    }
+}
@@ -0,0 +1,78 @@
+//! A window onto the plane, for state kept by position rather than by index
+//! into the board, like the cellule ages and the heat map.
+
+/// Where a board sits on the plane: the position of its first cellule and
+/// its size.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BoardWindow {
+    pub origin: (i64, i64),
+    pub width: usize,
+    pub height: usize,
+}
+
+impl BoardWindow {
+    pub fn new(origin: (i64, i64), width: usize, height: usize) -> BoardWindow {
+        BoardWindow {
+            origin,
+            width,
+            height,
+        }
+    }
+
+    /// The window of a board `width` cellules wide.
+    pub fn of_board(origin: (i64, i64), width: usize, board: &[bool]) -> BoardWindow {
+        BoardWindow::new(origin, width, board.len() / width.max(1))
+    }
+
+    pub fn contains(&self, (x, y): (i64, i64)) -> bool {
+        x >= self.origin.0
+            && y >= self.origin.1
+            && x < self.origin.0 + self.width as i64
+            && y < self.origin.1 + self.height as i64
+    }
+
+    /// The position of every cellule, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (i64, i64)> {
+        let BoardWindow { origin, width, .. } = *self;
+        (0..width * self.height).map(move |index| {
+            (
+                origin.0 + (index % width) as i64,
+                origin.1 + (index / width) as i64,
+            )
+        })
+    }
+
+    /// The positions of the alive cellules of a board in this window.
+    pub fn alive_positions<'a>(&self, board: &'a [bool]) -> impl Iterator<Item = (i64, i64)> + 'a {
+        self.positions()
+            .zip(board.iter())
+            .filter(|(_, alive)| **alive)
+            .map(|(position, _)| position)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let window = BoardWindow::of_board((-1, 2), 2, &[false, true, true, false]);
+
+        assert_eq!(window, BoardWindow::new((-1, 2), 2, 2));
+        assert_eq!(
+            window.positions().collect::<Vec<(i64, i64)>>(),
+            vec![(-1, 2), (0, 2), (-1, 3), (0, 3)]
+        );
+        assert_eq!(
+            window
+                .alive_positions(&[false, true, true, false])
+                .collect::<Vec<(i64, i64)>>(),
+            vec![(0, 2), (-1, 3)]
+        );
+        assert!(window.contains((0, 3)));
+        assert!(!window.contains((1, 3)));
+        assert!(!window.contains((-1, 4)));
+    }
+}
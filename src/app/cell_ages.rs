@@ -0,0 +1,81 @@
+//! How many generations each alive cellule has been alive without a break,
+//! for coloring cellules by age.
+//!
+//! Ages are kept by position on the plane, so they survive the infinite
+//! plane's viewport moving around. Boards are passed in as a window: the
+//! position of their first cellule, their width and their alive flags.
+
+use std::collections::HashMap;
+
+use super::board_window::BoardWindow;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CellAges {
+    ages: HashMap<(i64, i64), u32>,
+}
+
+impl CellAges {
+    pub fn clear(&mut self) {
+        self.ages.clear();
+    }
+
+    /// Takes in the next generation: survivors get a generation older and
+    /// newborn cellules start at 0.
+    pub fn advance(&mut self, origin: (i64, i64), width: usize, board: &[bool]) {
+        self.ages = BoardWindow::of_board(origin, width, board)
+            .alive_positions(board)
+            .map(|position| {
+                let age = self.ages.get(&position).map_or(0, |age| age + 1);
+                (position, age)
+            })
+            .collect();
+    }
+
+    /// Takes in edits made to the current generation. Cellules brought to
+    /// life are newborn, the others keep their age.
+    pub fn amend(&mut self, origin: (i64, i64), width: usize, board: &[bool]) {
+        self.ages = BoardWindow::of_board(origin, width, board)
+            .alive_positions(board)
+            .map(|position| (position, self.ages.get(&position).copied().unwrap_or(0)))
+            .collect();
+    }
+
+    /// The age of every cellule in a window, 0 for dead ones.
+    pub fn ages_in(&self, origin: (i64, i64), width: usize, height: usize) -> Vec<u32> {
+        BoardWindow::new(origin, width, height)
+            .positions()
+            .map(|position| self.ages.get(&position).copied().unwrap_or(0))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_counts_generations_alive() {
+        let mut ages = CellAges::default();
+        ages.advance((0, 0), 2, &[true, true, false, false]);
+        ages.advance((0, 0), 2, &[true, false, true, false]);
+        ages.amend((0, 0), 2, &[true, false, true, true]);
+        ages.advance((0, 0), 2, &[true, false, true, true]);
+
+        assert_eq!(ages.ages_in((0, 0), 2, 2), vec![2, 0, 1, 1]);
+
+        ages.clear();
+        assert_eq!(ages.ages_in((0, 0), 2, 2), vec![0; 4]);
+    }
+
+    #[test]
+    fn test_keeps_ages_when_the_window_moves() {
+        let mut ages = CellAges::default();
+        ages.advance((0, 0), 3, &[false, true, false]);
+        // The same cellule, seen from a window one to the left and one up.
+        ages.advance((-1, -1), 3, &[false, false, false, false, false, true]);
+
+        assert_eq!(ages.ages_in((1, 0), 2, 1), vec![1, 0]);
+        assert_eq!(ages.ages_in((0, 0), 3, 1), vec![0, 1, 0]);
+    }
+}
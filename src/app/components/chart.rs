@@ -0,0 +1,228 @@
+use yewtil::NeqAssign;
+
+#[allow(dead_code)]
+use log::*;
+use wasm_bindgen::JsValue;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use yew::prelude::*;
+
+use crate::app::population::{nearest_sample, GenerationStats};
+
+const CHART_WIDTH: f64 = 1000.0;
+const CHART_HEIGHT: f64 = 160.0;
+const CHART_PADDING: f64 = 8.0;
+
+const POPULATION_COLOR: &str = "rgb(246, 157, 60)";
+const BIRTHS_COLOR: &str = "#2cb45a";
+const DEATHS_COLOR: &str = "#f65c3c";
+const MARKER_COLOR: &str = "rgba(0, 0, 0, 0.42)";
+
+pub enum Msg {
+  Hover(MouseEvent),
+  Leave,
+}
+
+#[derive(PartialEq, Clone, Properties)]
+pub struct Props {
+  #[prop_or_default]
+  pub samples: Vec<GenerationStats>,
+
+  /// The generation on the grid, marked on the chart.
+  #[prop_or_default]
+  pub current_generation: i32,
+}
+
+/// Population, births and deaths of the current run, one point per
+/// generation, on a canvas of its own under the grid.
+pub struct PopulationChart {
+  props: Props,
+  canvas_ref: NodeRef,
+  link: ComponentLink<Self>,
+  /// The hovered generation and where the pointer is, in CSS pixels.
+  hovered: Option<(GenerationStats, i32)>,
+}
+
+impl Component for PopulationChart {
+  type Message = Msg;
+  type Properties = Props;
+
+  fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+    Self {
+      props,
+      canvas_ref: NodeRef::default(),
+      link,
+      hovered: None,
+    }
+  }
+
+  fn update(&mut self, msg: Self::Message) -> ShouldRender {
+    match msg {
+      Msg::Hover(mouse_event) => {
+        let canvas_element = match self.canvas_ref.cast::<HtmlCanvasElement>() {
+          Some(canvas_element) => canvas_element,
+          None => return false,
+        };
+
+        let x = mouse_event.offset_x() as f64 * CHART_WIDTH / canvas_element.client_width() as f64;
+        let generation = self.generation_at(x);
+        self.hovered = nearest_sample(&self.props.samples, generation)
+          .map(|sample| (*sample, mouse_event.offset_x()));
+      }
+      Msg::Leave => {
+        self.hovered = None;
+      }
+    }
+
+    true
+  }
+
+  fn change(&mut self, props: Self::Properties) -> ShouldRender {
+    if self.props.neq_assign(props) {
+      // A new run starts without a hovered generation.
+      if self.props.samples.len() <= 1 {
+        self.hovered = None;
+      }
+      true
+    } else {
+      false
+    }
+  }
+
+  fn rendered(&mut self, _first_render: bool) {
+    if let Some(canvas_element) = self.canvas_ref.cast::<HtmlCanvasElement>() {
+      self.render_canvas(canvas_element);
+    }
+  }
+
+  fn view(&self) -> Html {
+    html! {
+      <div class="population-chart">
+        <canvas
+          class="population-chart-canvas"
+          ref=self.canvas_ref.clone()
+          width=CHART_WIDTH as i32
+          height=CHART_HEIGHT as i32
+          onmousemove=self.link.callback(|event: MouseEvent| Msg::Hover(event))
+          onmouseleave=self.link.callback(|_| Msg::Leave)
+        ></canvas>
+        <div class="population-chart-legend">
+          <span class="legend-population">{"Population"}</span>
+          <span class="legend-births">{"Births"}</span>
+          <span class="legend-deaths">{"Deaths"}</span>
+        </div>
+        {self.view_tooltip()}
+      </div>
+    }
+  }
+}
+
+impl PopulationChart {
+  fn view_tooltip(&self) -> Html {
+    match self.hovered {
+      Some((sample, offset_x)) => html! {
+        <div class="population-chart-tooltip" style=format!("left: {}px", offset_x)>
+          <div>{format!("Generation {}", sample.generation)}</div>
+          <div>{format!("Population {}", sample.population)}</div>
+          {if sample.jumped {
+            html! { <div>{"Jumped here"}</div> }
+          } else {
+            html! {
+              <>
+                <div>{format!("Births {}", sample.births)}</div>
+                <div>{format!("Deaths {}", sample.deaths)}</div>
+              </>
+            }
+          }}
+        </div>
+      },
+      None => html! {},
+    }
+  }
+
+  fn last_generation(&self) -> i32 {
+    self
+      .props
+      .samples
+      .last()
+      .map(|sample| sample.generation)
+      .unwrap_or(0)
+      .max(self.props.current_generation)
+      .max(1)
+  }
+
+  fn x_of(&self, generation: i32) -> f64 {
+    CHART_PADDING
+      + (generation as f64 / self.last_generation() as f64) * (CHART_WIDTH - 2.0 * CHART_PADDING)
+  }
+
+  fn generation_at(&self, x: f64) -> i32 {
+    let share = (x - CHART_PADDING) / (CHART_WIDTH - 2.0 * CHART_PADDING);
+    (share * self.last_generation() as f64).round() as i32
+  }
+
+  fn render_canvas(&self, canvas_element: HtmlCanvasElement) {
+    let ctx = CanvasRenderingContext2d::from(JsValue::from(
+      canvas_element.get_context("2d").unwrap().unwrap(),
+    ));
+    ctx.clear_rect(0.0, 0.0, CHART_WIDTH, CHART_HEIGHT);
+
+    let samples = &self.props.samples;
+    if samples.is_empty() {
+      return;
+    }
+
+    let highest = samples
+      .iter()
+      .map(|sample| sample.population.max(sample.births).max(sample.deaths))
+      .max()
+      .unwrap_or(0)
+      .max(1) as f64;
+    let y_of = |value: usize| {
+      CHART_HEIGHT - CHART_PADDING - (value as f64 / highest) * (CHART_HEIGHT - 2.0 * CHART_PADDING)
+    };
+
+    // Color, whether jumps leave the value known, and the value.
+    let series: [(&str, bool, fn(&GenerationStats) -> usize); 3] = [
+      (BIRTHS_COLOR, false, |sample| sample.births),
+      (DEATHS_COLOR, false, |sample| sample.deaths),
+      (POPULATION_COLOR, true, |sample| sample.population),
+    ];
+    for (color, known_after_jumps, value) in series.iter() {
+      ctx.set_stroke_style(&JsValue::from_str(color));
+      ctx.set_line_width(2.0);
+      ctx.begin_path();
+      let mut joined = false;
+      for sample in samples.iter() {
+        // A jump breaks the line, and skips the points it has no value for.
+        if sample.jumped {
+          joined = false;
+          if !known_after_jumps {
+            continue;
+          }
+        }
+        let (x, y) = (self.x_of(sample.generation), y_of(value(sample)));
+        if joined {
+          ctx.line_to(x, y);
+        } else {
+          ctx.move_to(x, y);
+        }
+        joined = true;
+      }
+      ctx.stroke();
+    }
+
+    let mut markers = vec![self.props.current_generation];
+    if let Some((sample, _)) = self.hovered {
+      markers.push(sample.generation);
+    }
+    ctx.set_stroke_style(&JsValue::from_str(MARKER_COLOR));
+    ctx.set_line_width(1.0);
+    for generation in markers {
+      let x = self.x_of(generation);
+      ctx.begin_path();
+      ctx.move_to(x, 0.0);
+      ctx.line_to(x, CHART_HEIGHT);
+      ctx.stroke();
+    }
+  }
+}
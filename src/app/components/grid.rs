@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 // use crate::app::core::game::{Cellule, LifeState};
 use game_of_life_core::core::game::{Cellule, LifeState};
+use serde_derive::{Deserialize, Serialize};
 use yewtil::NeqAssign;
 
+use cellule_life_engine::geometry::Geometry;
 use crate::utils::colors::*;
+use crate::utils::theme::{css_color, Theme, LIGHT};
 
 #[allow(dead_code)]
 use log::*;
@@ -13,11 +16,40 @@ use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 use yew::prelude::*;
 
 const BASE_CELLULE_SIZE: i32 = 20;
+/// Cellules this many generations old or older get the end of the gradient.
+const AGE_SPAN: u32 = 100;
+const HEAT_MAP_OPACITY: f32 = 0.6;
+/// Halos in a color of their own are see-through, so they don't hide the
+/// cellules under them.
+const HALO_OPACITY: f32 = 0.5;
 
 pub enum Msg {
   Click(MouseEvent),
 }
 
+/// What the gradient of alive cellules shows.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ColorMode {
+  /// Where the cellule is on the board.
+  #[default]
+  Position,
+  /// How many generations the cellule has been alive.
+  Age,
+}
+
+impl ColorMode {
+  pub fn all() -> Vec<ColorMode> {
+    vec![ColorMode::Position, ColorMode::Age]
+  }
+
+  pub fn label(&self) -> &'static str {
+    match self {
+      ColorMode::Position => "Color by position",
+      ColorMode::Age => "Color by age",
+    }
+  }
+}
+
 #[derive(PartialEq, Clone, Properties)]
 pub struct Props {
   #[prop_or_default]
@@ -38,6 +70,38 @@ pub struct Props {
   #[prop_or_default]
   pub cellule_neighbors: HashMap<usize, Vec<Cellule>>,
 
+  #[prop_or_default]
+  pub decay_states: Vec<u8>,
+
+  #[prop_or_default]
+  pub state_count: u8,
+
+  #[prop_or_default]
+  pub neighbor_densities: Vec<f32>,
+
+  #[prop_or_default]
+  pub geometry: Geometry,
+
+  /// Generations each cellule has been alive, for `ColorMode::Age`.
+  #[prop_or_default]
+  pub ages: Vec<u32>,
+
+  #[prop_or_default]
+  pub color_mode: ColorMode,
+
+  #[prop_or(PALETTES[0])]
+  pub palette: Palette,
+
+  #[prop_or(LIGHT)]
+  pub theme: Theme,
+
+  /// How often each cellule changed state during the run.
+  #[prop_or_default]
+  pub heat: Vec<u32>,
+
+  #[prop_or_default]
+  pub showing_heat_map: bool,
+
   #[prop_or_default]
   pub onclick: Callback<(i32, i32)>,
 }
@@ -65,11 +129,10 @@ impl Component for GameGrid {
       Msg::Click(mouse_event) => {
         let canvas_element = self.canvas_ref.cast::<HtmlCanvasElement>().unwrap();
 
-        let translation_ratio_x = ((BASE_CELLULE_SIZE as f32) * self.props.cellules_width as f32)
-          / canvas_element.client_width() as f32;
+        let (canvas_width, canvas_height) = self.canvas_size();
 
-        let translation_ratio_y = ((BASE_CELLULE_SIZE as f32) * self.props.cellules_height as f32)
-          / canvas_element.client_height() as f32;
+        let translation_ratio_x = canvas_width / canvas_element.client_width() as f64;
+        let translation_ratio_y = canvas_height / canvas_element.client_height() as f64;
 
         self.render_canvas(canvas_element);
 
@@ -79,12 +142,20 @@ impl Component for GameGrid {
           mouse_event.offset_y()
         );
 
-        let column_number =
-          ((mouse_event.offset_x() as f32) * translation_ratio_x / BASE_CELLULE_SIZE as f32) as i32;
-        let row_number =
-          ((mouse_event.offset_y() as f32) * translation_ratio_y / BASE_CELLULE_SIZE as f32) as i32;
+        let cellule = self.props.geometry.cellule_at(
+          (mouse_event.offset_x() as f64) * translation_ratio_x,
+          (mouse_event.offset_y() as f64) * translation_ratio_y,
+          self.props.cellules_width,
+          self.props.cellules_height,
+          BASE_CELLULE_SIZE as f64,
+        );
 
-        self.props.onclick.emit((column_number, row_number));
+        if let Some((column_number, row_number)) = cellule {
+          self
+            .props
+            .onclick
+            .emit((column_number as i32, row_number as i32));
+        }
 
         true
       }
@@ -103,8 +174,9 @@ impl Component for GameGrid {
   }
 
   fn view(&self) -> Html {
-    let canvas_height = (self.props.cellules_height as i32) * BASE_CELLULE_SIZE;
-    let canvas_width = (self.props.cellules_width as i32) * BASE_CELLULE_SIZE;
+    let (canvas_width, canvas_height) = self.canvas_size();
+    let canvas_width = canvas_width.ceil() as i32;
+    let canvas_height = canvas_height.ceil() as i32;
 
     html! {
       <>
@@ -121,74 +193,232 @@ impl Component for GameGrid {
 }
 
 impl GameGrid {
+  fn canvas_size(&self) -> (f64, f64) {
+    self.props.geometry.canvas_size(
+      self.props.cellules_width,
+      self.props.cellules_height,
+      BASE_CELLULE_SIZE as f64,
+    )
+  }
+
   fn render_canvas(&self, canvas_element: HtmlCanvasElement) -> HtmlCanvasElement {
     let ctx = CanvasRenderingContext2d::from(JsValue::from(
       canvas_element.get_context("2d").unwrap().unwrap(),
     ));
-    ctx.set_fill_style(&JsValue::from_str("#aaaadd"));
+    ctx.set_fill_style(&self.background_style());
 
-    ctx.fill_rect(
-      0.0,
-      0.0,
-      BASE_CELLULE_SIZE as f64 * (self.props.cellules_width as f64),
-      BASE_CELLULE_SIZE as f64 * (self.props.cellules_height as f64),
-    );
+    let (canvas_width, canvas_height) = self.canvas_size();
+    ctx.fill_rect(0.0, 0.0, canvas_width, canvas_height);
 
-    let gradient_manager = GradientManager::new(
-      Color {
-        red: 246.0,
-        green: 157.0,
-        blue: 60.0,
-      },
-      Color {
-        red: 63.0,
-        green: 135.0,
-        blue: 166.0,
-      },
-    );
+    let gradient_manager = self.props.palette.gradient();
 
-    for cellule_index in 0..2000 {
+    for cellule_index in 0..(self.props.cellules_width * self.props.cellules_height) {
       let row_number = cellule_index / self.props.cellules_width;
       let column_number = cellule_index % self.props.cellules_width;
 
       let x = (BASE_CELLULE_SIZE as f32) * (column_number as f32);
       let y = (BASE_CELLULE_SIZE as f32) * (row_number as f32);
 
+      let progress_percentage = match self.props.color_mode {
+        ColorMode::Position => (column_number + row_number) as f32 / 100.0,
+        ColorMode::Age => {
+          let age = self.props.ages.get(cellule_index).copied().unwrap_or(0);
+          age as f32 / AGE_SPAN as f32
+        }
+      };
+      let color = gradient_manager.interpolate_colors(progress_percentage);
+
       if self.props.cellules[cellule_index].life_state == LifeState::Alive {
         // "rgb()"
         // let new_color = format!("rgb({}, {}, {})", newRed, newGreen, newBlue);
 
-        let added_indexes = (column_number + row_number) as f32;
-        let progress_percentage = added_indexes / 100.0;
-        let color = gradient_manager.interpolate_colors(progress_percentage);
         let new_color = format!("rgb({}, {}, {})", color.red, color.green, color.blue);
         ctx.set_fill_style(&JsValue::from_str(new_color.as_str()));
 
-        ctx.begin_path();
+        self.fill_cellule(&ctx, column_number, row_number);
+
+        if let Some(halo) = self.props.theme.halo {
+          let halo_color = format!(
+            "rgba({}, {}, {}, {})",
+            halo.red, halo.green, halo.blue, HALO_OPACITY
+          );
+          ctx.set_fill_style(&JsValue::from_str(halo_color.as_str()));
+        }
+
+        // The neighbor halos only line up on the square grid.
+        if let Some(&density) = self.props.neighbor_densities.get(cellule_index) {
+          self.draw_density_halo(&ctx, density as f64, x as f64, y as f64);
+        } else if self.props.geometry == Geometry::Square {
+          self.draw_neighbors(&ctx, cellule_index, x as f64, y as f64);
+        }
+
+        ctx.set_fill_style(&self.background_style());
+      } else if let Some(&state) = self.props.decay_states.get(cellule_index) {
+        if state > 1 {
+          self.draw_decaying_cellule(&ctx, color, state, column_number, row_number);
+        }
+      }
+    }
+
+    if let Some(grid_lines) = self.props.theme.grid_lines {
+      self.draw_grid_lines(&ctx, grid_lines);
+    }
 
-        let radius = (BASE_CELLULE_SIZE as f64) / 2.0;
+    if self.props.showing_heat_map {
+      self.draw_heat_map(&ctx);
+    }
+
+    canvas_element
+  }
 
-        ctx
-          .ellipse(
-            x as f64 + radius,
-            y as f64 + radius,
-            radius,
-            radius,
-            0.0,
-            0.0,
-            6.29,
-          )
-          .unwrap();
-        ctx.fill();
-        ctx.close_path();
+  fn background_style(&self) -> JsValue {
+    JsValue::from_str(&css_color(self.props.theme.background))
+  }
 
-        self.draw_neighbors(&ctx, cellule_index, x as f64, y as f64);
+  /// Outlines every cellule: straight lines across the square grid, each
+  /// cellule's outline on the others.
+  fn draw_grid_lines(&self, ctx: &CanvasRenderingContext2d, color: Color) {
+    ctx.set_stroke_style(&JsValue::from_str(&css_color(color)));
+    ctx.set_line_width(1.0);
+    ctx.begin_path();
 
-        ctx.set_fill_style(&JsValue::from_str("#aaaadd"));
+    let size = BASE_CELLULE_SIZE as f64;
+    if self.props.geometry == Geometry::Square {
+      let (canvas_width, canvas_height) = self.canvas_size();
+      for column_number in 0..=self.props.cellules_width {
+        ctx.move_to(column_number as f64 * size, 0.0);
+        ctx.line_to(column_number as f64 * size, canvas_height);
+      }
+      for row_number in 0..=self.props.cellules_height {
+        ctx.move_to(0.0, row_number as f64 * size);
+        ctx.line_to(canvas_width, row_number as f64 * size);
+      }
+    } else {
+      for cellule_index in 0..(self.props.cellules_width * self.props.cellules_height) {
+        let outline = self.props.geometry.cellule_outline(
+          cellule_index % self.props.cellules_width,
+          cellule_index / self.props.cellules_width,
+          size,
+        );
+        if let Some((x, y)) = outline.first() {
+          ctx.move_to(*x, *y);
+          for (x, y) in outline.iter().skip(1) {
+            ctx.line_to(*x, *y);
+          }
+          ctx.close_path();
+        }
       }
     }
 
-    canvas_element
+    ctx.stroke();
+  }
+
+  /// Covers the board with how busy each cellule was, the busiest in the
+  /// hot end of the ramp. Cellules that never changed are left uncovered.
+  fn draw_heat_map(&self, ctx: &CanvasRenderingContext2d) {
+    let busiest = self.props.heat.iter().copied().max().unwrap_or(0).max(1);
+    let heat_gradient = HEAT_RAMP.gradient();
+
+    for (cellule_index, &count) in self.props.heat.iter().enumerate() {
+      if count == 0 {
+        continue;
+      }
+
+      let color = heat_gradient.interpolate_colors(count as f32 / busiest as f32);
+      let new_color = format!(
+        "rgba({}, {}, {}, {})",
+        color.red, color.green, color.blue, HEAT_MAP_OPACITY
+      );
+      ctx.set_fill_style(&JsValue::from_str(new_color.as_str()));
+
+      self.fill_cellule_area(
+        ctx,
+        cellule_index % self.props.cellules_width,
+        cellule_index / self.props.cellules_width,
+      );
+    }
+
+    ctx.set_fill_style(&self.background_style());
+  }
+
+  /// Fills the whole area of one cellule, a square on the square grid.
+  fn fill_cellule_area(
+    &self,
+    ctx: &CanvasRenderingContext2d,
+    column_number: usize,
+    row_number: usize,
+  ) {
+    let outline =
+      self
+        .props
+        .geometry
+        .cellule_outline(column_number, row_number, BASE_CELLULE_SIZE as f64);
+
+    if outline.is_empty() {
+      ctx.fill_rect(
+        (BASE_CELLULE_SIZE as f64) * (column_number as f64),
+        (BASE_CELLULE_SIZE as f64) * (row_number as f64),
+        BASE_CELLULE_SIZE as f64,
+        BASE_CELLULE_SIZE as f64,
+      );
+    } else {
+      self.fill_cellule(ctx, column_number, row_number);
+    }
+  }
+
+  /// Dying cellules of Generations rules fade from their alive color into the
+  /// background as they count down to dead.
+  fn draw_decaying_cellule(
+    &self,
+    ctx: &CanvasRenderingContext2d,
+    color: Color,
+    state: u8,
+    column_number: usize,
+    row_number: usize,
+  ) {
+    let fade_manager = GradientManager::new(color, self.props.theme.background);
+    let progress_percentage = (state - 1) as f32 / (self.props.state_count.max(2) - 1) as f32;
+    let faded_color = fade_manager.interpolate_colors(progress_percentage);
+    let new_color = format!(
+      "rgb({}, {}, {})",
+      faded_color.red, faded_color.green, faded_color.blue
+    );
+    ctx.set_fill_style(&JsValue::from_str(new_color.as_str()));
+
+    self.fill_cellule(ctx, column_number, row_number);
+
+    ctx.set_fill_style(&self.background_style());
+  }
+
+  /// Fills one cellule with the current fill style: a circle on the square
+  /// grid, the cellule's outline on the others.
+  fn fill_cellule(&self, ctx: &CanvasRenderingContext2d, column_number: usize, row_number: usize) {
+    ctx.begin_path();
+
+    let outline =
+      self
+        .props
+        .geometry
+        .cellule_outline(column_number, row_number, BASE_CELLULE_SIZE as f64);
+
+    if outline.is_empty() {
+      let radius = (BASE_CELLULE_SIZE as f64) / 2.0;
+      let (x, y) =
+        self
+          .props
+          .geometry
+          .cellule_centre(column_number, row_number, BASE_CELLULE_SIZE as f64);
+
+      ctx.ellipse(x, y, radius, radius, 0.0, 0.0, 6.29).unwrap();
+    } else {
+      ctx.move_to(outline[0].0, outline[0].1);
+      for (x, y) in outline.iter().skip(1) {
+        ctx.line_to(*x, *y);
+      }
+    }
+
+    ctx.fill();
+    ctx.close_path();
   }
 
   fn draw_neighbors(
@@ -200,7 +430,21 @@ impl GameGrid {
   ) {
     let neighbors = self.props.cellule_neighbors.get(&cellule_index);
 
-    if neighbors.is_some() {
+    // Only the eight directions around a cellule have a halo of their own;
+    // anything bigger gets one halo sized by how many neighbors are alive.
+    if let Some(neighbors) = neighbors.filter(|neighbors| neighbors.len() > 8) {
+      let alive_count = neighbors
+        .iter()
+        .filter(|cellule| cellule.life_state == LifeState::Alive)
+        .count();
+
+      self.draw_density_halo(
+        ctx,
+        alive_count as f64 / neighbors.len() as f64,
+        cellule_x,
+        cellule_y,
+      );
+    } else if neighbors.is_some() {
       neighbors
         .unwrap()
         .iter()
@@ -243,6 +487,40 @@ impl GameGrid {
     )
   }
 
+  /// A single halo that grows with the share of the neighborhood that is
+  /// alive, for neighborhoods too large to draw neighbor by neighbor.
+  fn draw_density_halo(
+    &self,
+    ctx: &CanvasRenderingContext2d,
+    density: f64,
+    cellule_x: f64,
+    cellule_y: f64,
+  ) {
+    if density <= 0.0 {
+      return;
+    }
+
+    let radius = (BASE_CELLULE_SIZE as f64) / 2.0;
+    let staggered_step =
+      (((js_sys::Date::now() / 200.0) + cellule_x + cellule_y).sin() / 5.0) + 0.1;
+    let radius_modifier = 1.0 + density.min(1.0) * (1.0 + staggered_step);
+
+    ctx.begin_path();
+    ctx
+      .ellipse(
+        cellule_x + radius,
+        cellule_y + radius,
+        radius * radius_modifier,
+        radius * radius_modifier,
+        0.0,
+        0.0,
+        6.29,
+      )
+      .unwrap();
+    ctx.fill();
+    ctx.close_path();
+  }
+
   fn draw_neighbor_ellipse(&self, ctx: &CanvasRenderingContext2d, x: f64, y: f64) {
     let staggered_step = (((js_sys::Date::now() / 200.0) + x + y).sin() / 5.0) + 0.1;
     let step = staggered_step;
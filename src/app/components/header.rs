@@ -7,14 +7,33 @@ use yew::services::storage::{Area, StorageService};
 use yewtil::NeqAssign;
 
 use crate::app::components::fps::FpsDetector;
+use crate::app::components::grid::ColorMode;
+use crate::utils::colors::{Palette, PALETTES};
+use crate::utils::theme::{Theme, THEMES};
+use cellule_life_engine::geometry::Geometry;
+use cellule_life_engine::rules::Rule;
+use cellule_life_engine::sparse::BoundingBox;
+use cellule_life_engine::Topology;
 use game_of_life_core::core::seeds::seeds::Seed;
 
 #[derive(Clone, PartialEq)]
 pub enum Msg {
     Reset,
+    OpenSlots,
+    OpenStats,
     SeedChanged(usize),
     UpdateRate(String),
     ToggleConfig,
+    ToggleTopology,
+    RuleChanged(usize),
+    GeometryChanged(usize),
+    ColorModeChanged(usize),
+    PaletteChanged(usize),
+    ThemeChanged(usize),
+    EditBudgetChanged(usize),
+    UpdateJumpTarget(String),
+    Jump,
+    Nope,
 }
 
 #[derive(Properties, Clone, PartialEq)]
@@ -30,21 +49,75 @@ pub struct Props {
     pub modification_count: i32,
     #[prop_or_default]
     pub step_count: i32,
+    #[prop_or_default]
+    pub population: usize,
+    #[prop_or_default]
+    pub bounding_box: Option<BoundingBox>,
 
     #[prop_or_default]
     pub on_reset: Callback<()>,
     #[prop_or_default]
+    pub on_open_slots: Callback<()>,
+    #[prop_or_default]
+    pub on_open_stats: Callback<()>,
+    #[prop_or_default]
     pub on_seed_change: Callback<Seed>,
     #[prop_or_default]
     pub on_rate_change: Callback<f64>,
+    #[prop_or_default]
+    pub on_jump: Callback<u64>,
+    #[prop_or_default]
+    pub on_topology_change: Callback<Topology>,
+    #[prop_or_default]
+    pub on_rule_change: Callback<Rule>,
+    #[prop_or_default]
+    pub on_geometry_change: Callback<Geometry>,
+    #[prop_or_default]
+    pub on_edit_budget_change: Callback<Option<usize>>,
+    #[prop_or_default]
+    pub on_color_mode_change: Callback<ColorMode>,
+    #[prop_or_default]
+    pub on_palette_change: Callback<Option<Palette>>,
+    #[prop_or_default]
+    pub on_theme_change: Callback<Option<Theme>>,
 
     #[prop_or_default]
     pub seed_options: Vec<Seed>,
+    /// The seed being played, picked from `seed_options`.
+    pub current_seed: Seed,
+    #[prop_or(60.0)]
+    pub rate: f64,
 
     #[prop_or_default]
     pub max_fps: i64,
+
+    #[prop_or_default]
+    pub topology: Topology,
+
+    #[prop_or_default]
+    pub rule: Rule,
+
+    #[prop_or_default]
+    pub geometry: Geometry,
+
+    #[prop_or_default]
+    pub edit_budget: Option<usize>,
+
+    #[prop_or_default]
+    pub color_mode: ColorMode,
+
+    /// The palette the player picked, `None` for the theme's.
+    #[prop_or_default]
+    pub palette: Option<Palette>,
+
+    /// The theme the player picked, `None` to follow the system.
+    #[prop_or_default]
+    pub theme: Option<Theme>,
 }
 
+/// The edit budgets on offer. `None` allows any number of edits.
+const EDIT_BUDGETS: [Option<usize>; 4] = [None, Some(3), Some(5), Some(10)];
+
 pub struct AppHeader {
     props: Props,
     link: ComponentLink<Self>,
@@ -52,6 +125,7 @@ pub struct AppHeader {
     rate: f64,
     created_timestamp: f64,
     showing_config: bool,
+    jump_target: String,
 }
 
 impl Component for AppHeader {
@@ -61,15 +135,13 @@ impl Component for AppHeader {
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         // info!("CREATED");
         Self {
+            current_seed: props.current_seed.clone(),
+            rate: props.rate,
             props,
             link,
-            current_seed: Seed {
-                cellules: vec![],
-                label: "Loading".to_owned(),
-            },
-            rate: 60.0,
             created_timestamp: js_sys::Date::now(),
             showing_config: false,
+            jump_target: "".to_owned(),
         }
     }
 
@@ -80,6 +152,12 @@ impl Component for AppHeader {
             Msg::Reset => {
                 self.props.on_seed_change.emit(self.current_seed.clone());
             }
+            Msg::OpenSlots => {
+                self.props.on_open_slots.emit(());
+            }
+            Msg::OpenStats => {
+                self.props.on_open_stats.emit(());
+            }
             Msg::SeedChanged(seed_option_index) => {
                 let seed_option = self.props.seed_options[seed_option_index].clone();
                 self.current_seed = seed_option.clone();
@@ -93,6 +171,50 @@ impl Component for AppHeader {
             Msg::ToggleConfig => {
                 self.showing_config = !self.showing_config;
             }
+            Msg::ToggleTopology => {
+                let topology = match self.props.topology {
+                    Topology::Bounded => Topology::Infinite,
+                    Topology::Infinite => Topology::Bounded,
+                };
+                self.props.on_topology_change.emit(topology);
+            }
+            Msg::RuleChanged(rule_index) => {
+                let (_, rule) = self.props.geometry.rule_presets()[rule_index].clone();
+                self.props.on_rule_change.emit(rule);
+            }
+            Msg::GeometryChanged(geometry_index) => {
+                self.props
+                    .on_geometry_change
+                    .emit(Geometry::all()[geometry_index]);
+            }
+            Msg::ColorModeChanged(color_mode_index) => {
+                self.props
+                    .on_color_mode_change
+                    .emit(ColorMode::all()[color_mode_index]);
+            }
+            // The first option of both is the default.
+            Msg::PaletteChanged(palette_index) => {
+                let palette = palette_index.checked_sub(1).map(|index| PALETTES[index]);
+                self.props.on_palette_change.emit(palette);
+            }
+            Msg::ThemeChanged(theme_index) => {
+                let theme = theme_index.checked_sub(1).map(|index| THEMES[index]);
+                self.props.on_theme_change.emit(theme);
+            }
+            Msg::EditBudgetChanged(edit_budget_index) => {
+                self.props
+                    .on_edit_budget_change
+                    .emit(EDIT_BUDGETS[edit_budget_index]);
+            }
+            Msg::UpdateJumpTarget(jump_target) => {
+                self.jump_target = jump_target;
+            }
+            Msg::Jump => {
+                if let Ok(generation) = self.jump_target.parse::<u64>() {
+                    self.props.on_jump.emit(generation);
+                }
+            }
+            Msg::Nope => {}
         }
 
         true
@@ -101,12 +223,9 @@ impl Component for AppHeader {
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
         if self.props.neq_assign(props) {
             // info!("CHANGE");
-            if self.current_seed.label == "Loading".to_owned() {
-                self.current_seed = self.props.seed_options[0].clone();
-                self.props
-                    .on_seed_change
-                    .emit(self.props.seed_options[0].clone());
-            }
+            // The app picks the seed itself when the geometry changes or a
+            // save slot is loaded.
+            self.current_seed = self.props.current_seed.clone();
 
             if self.rate > self.props.max_fps as f64 {
                 self.rate = self.props.max_fps as f64;
@@ -134,15 +253,130 @@ impl Component for AppHeader {
                             ChangeData::Select(element) => {
                                 Msg::SeedChanged(element.selected_index() as usize)
                             }
-                            _ => unimplemented!()
+                            _ => Msg::Nope
                         })>
                             {self.props.seed_options.iter().map(|seed_option| {
                                 html!(
-                                <option value={seed_option.label.clone()}>{seed_option.label.clone()}</option>
+                                <option value={seed_option.label.clone()} selected={seed_option.label == self.current_seed.label}>
+                                    {seed_option.label.clone()}
+                                </option>
                             )}).collect::<Html>()}
                         </select>
 
                         <button class="reset-button" onclick=self.link.callback(|_| Msg::Reset)>{"Reset"}</button>
+                        <button class="slots-button" onclick=self.link.callback(|_| Msg::OpenSlots)>{"Saves"}</button>
+                        <button class="stats-button" onclick=self.link.callback(|_| Msg::OpenStats)>{"Stats"}</button>
+
+                        <select class="geometry-select" onchange=self.link.callback(|event: ChangeData| match event {
+                            ChangeData::Select(element) => {
+                                Msg::GeometryChanged(element.selected_index() as usize)
+                            }
+                            _ => Msg::Nope
+                        })>
+                            {Geometry::all().into_iter().map(|geometry| {
+                                html!(
+                                <option value={geometry.label()} selected={geometry == self.props.geometry}>
+                                    {geometry.label()}
+                                </option>
+                            )}).collect::<Html>()}
+                        </select>
+
+                        <select class="rule-select" onchange=self.link.callback(|event: ChangeData| match event {
+                            ChangeData::Select(element) => {
+                                Msg::RuleChanged(element.selected_index() as usize)
+                            }
+                            _ => Msg::Nope
+                        })>
+                            {self.props.geometry.rule_presets().into_iter().map(|(label, rule)| {
+                                html!(
+                                <option value={rule.to_string()} selected={rule == self.props.rule}>
+                                    {format!("{} {}", label, rule)}
+                                </option>
+                            )}).collect::<Html>()}
+                        </select>
+
+                        <select class="edit-budget-select" onchange=self.link.callback(|event: ChangeData| match event {
+                            ChangeData::Select(element) => {
+                                Msg::EditBudgetChanged(element.selected_index() as usize)
+                            }
+                            _ => Msg::Nope
+                        })>
+                            {EDIT_BUDGETS.iter().map(|edit_budget| {
+                                let label = match edit_budget {
+                                    Some(edit_budget) => format!("{} edits", edit_budget),
+                                    None => "Unlimited edits".to_owned(),
+                                };
+                                html!(
+                                <option value={label.clone()} selected={*edit_budget == self.props.edit_budget}>
+                                    {label}
+                                </option>
+                            )}).collect::<Html>()}
+                        </select>
+
+                        <select class="color-mode-select" onchange=self.link.callback(|event: ChangeData| match event {
+                            ChangeData::Select(element) => {
+                                Msg::ColorModeChanged(element.selected_index() as usize)
+                            }
+                            _ => Msg::Nope
+                        })>
+                            {ColorMode::all().into_iter().map(|color_mode| {
+                                html!(
+                                <option value={color_mode.label()} selected={color_mode == self.props.color_mode}>
+                                    {color_mode.label()}
+                                </option>
+                            )}).collect::<Html>()}
+                        </select>
+
+                        <select class="palette-select" onchange=self.link.callback(|event: ChangeData| match event {
+                            ChangeData::Select(element) => {
+                                Msg::PaletteChanged(element.selected_index() as usize)
+                            }
+                            _ => Msg::Nope
+                        })>
+                            <option value="" selected={self.props.palette.is_none()}>{"Theme colors"}</option>
+                            {PALETTES.iter().map(|palette| {
+                                html!(
+                                <option value={palette.name} selected={Some(*palette) == self.props.palette}>
+                                    {palette.name}
+                                </option>
+                            )}).collect::<Html>()}
+                        </select>
+
+                        <select class="theme-select" onchange=self.link.callback(|event: ChangeData| match event {
+                            ChangeData::Select(element) => {
+                                Msg::ThemeChanged(element.selected_index() as usize)
+                            }
+                            _ => Msg::Nope
+                        })>
+                            <option value="" selected={self.props.theme.is_none()}>{"System theme"}</option>
+                            {THEMES.iter().map(|theme| {
+                                html!(
+                                <option value={theme.name} selected={Some(*theme) == self.props.theme}>
+                                    {format!("{} theme", theme.name)}
+                                </option>
+                            )}).collect::<Html>()}
+                        </select>
+
+                        <label class="topology-toggle">
+                            <input
+                                type="checkbox"
+                                checked={self.props.topology == Topology::Infinite}
+                                onclick=self.link.callback(|_| Msg::ToggleTopology)
+                            />
+                            {"Infinite"}
+                        </label>
+                    </div>
+
+                    <div class=format!("mobile-config-row jump-section {}", showing)>
+                        <input
+                            type="number"
+                            min="0"
+                            max={i32::MAX}
+                            placeholder="Generation"
+                            value={self.jump_target.clone()}
+                            oninput=self.link.callback(|event: InputData| Msg::UpdateJumpTarget(event.value))
+                        />
+                        <button class="jump-button" onclick=self.link.callback(|_| Msg::Jump)>{"Jump"}</button>
                     </div>
 
                     <div class="spacer"></div>
@@ -158,6 +392,7 @@ impl Component for AppHeader {
                                 min="10"
                                 max={self.props.max_fps}
                                 step="10"
+                                value={self.rate}
                                 oninput=self.link.callback(|event: InputData| Msg::UpdateRate(event.value))
                             />
                             <div class="slider-label-end">{self.props.max_fps}</div>
@@ -167,10 +402,7 @@ impl Component for AppHeader {
                     <div class="spacer"></div>
 
                     <div class="metrics">
-                        <div class="metric">
-                            <div class="metric-label">{"Edits"}</div>
-                            <div class="metric-value">{self.props.modification_count}</div>
-                        </div>
+                        {self.view_edits_metric()}
                         <div class="metric">
                             <div class="metric-label">{"Steps"}</div>
                             <div class="metric-value">{self.props.step_count}</div>
@@ -179,9 +411,54 @@ impl Component for AppHeader {
                             <div class="metric-label">{"Active"}</div>
                             <div class="metric-value">{self.props.active_count}</div>
                         </div>
+                        {self.view_plane_metrics()}
                     </div>
                 </header>
             </>
         }
     }
 }
+
+impl AppHeader {
+    /// Edits made, or the edits left when the run has a budget.
+    fn view_edits_metric(&self) -> Html {
+        let (label, value) = match self.props.edit_budget {
+            Some(edit_budget) => (
+                "Edits Left",
+                edit_budget.saturating_sub(self.props.modification_count as usize),
+            ),
+            None => ("Edits", self.props.modification_count as usize),
+        };
+
+        html! {
+            <div class="metric">
+                <div class="metric-label">{label}</div>
+                <div class="metric-value">{value}</div>
+            </div>
+        }
+    }
+
+    fn view_plane_metrics(&self) -> Html {
+        if self.props.topology != Topology::Infinite {
+            return html! {};
+        }
+
+        let bounds = match self.props.bounding_box {
+            Some(bounding_box) => format!("{}x{}", bounding_box.width(), bounding_box.height()),
+            None => "-".to_owned(),
+        };
+
+        html! {
+            <>
+                <div class="metric">
+                    <div class="metric-label">{"Population"}</div>
+                    <div class="metric-value">{self.props.population}</div>
+                </div>
+                <div class="metric">
+                    <div class="metric-label">{"Bounds"}</div>
+                    <div class="metric-value">{bounds}</div>
+                </div>
+            </>
+        }
+    }
+}
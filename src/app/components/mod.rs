@@ -1,3 +1,6 @@
+pub mod chart;
 pub mod grid;
 pub mod header;
-pub mod fps;
\ No newline at end of file
+pub mod fps;
+pub mod slots;
+pub mod stats;
\ No newline at end of file
@@ -0,0 +1,233 @@
+use yew::prelude::*;
+
+use yewtil::NeqAssign;
+
+use crate::app::slots::SaveSlot;
+
+#[derive(Clone, PartialEq)]
+pub enum Msg {
+    UpdateNewName(String),
+    Save,
+    StartRename(u32),
+    UpdateRename(String),
+    FinishRename,
+    CancelRename,
+    Load(u32),
+    Delete(u32),
+    Export(u32),
+    Close,
+    Nope,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    #[prop_or_default]
+    pub slots: Vec<SaveSlot>,
+    /// Runs can only be saved before they start or while paused.
+    #[prop_or_default]
+    pub can_save: bool,
+
+    #[prop_or_default]
+    pub on_save: Callback<String>,
+    #[prop_or_default]
+    pub on_load: Callback<u32>,
+    #[prop_or_default]
+    pub on_rename: Callback<(u32, String)>,
+    #[prop_or_default]
+    pub on_delete: Callback<u32>,
+    #[prop_or_default]
+    pub on_export: Callback<u32>,
+    #[prop_or_default]
+    pub on_close: Callback<()>,
+}
+
+/// The dialog that saves the current run to a named slot and manages the
+/// saved ones.
+pub struct SlotManager {
+    props: Props,
+    link: ComponentLink<Self>,
+    new_name: String,
+    renaming: Option<(u32, String)>,
+}
+
+impl Component for SlotManager {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            props,
+            link,
+            new_name: "".to_owned(),
+            renaming: None,
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::UpdateNewName(name) => {
+                self.new_name = name;
+            }
+            Msg::Save => {
+                let name = self.new_name.trim();
+                if name.is_empty() || !self.props.can_save {
+                    return false;
+                }
+                self.props.on_save.emit(name.to_owned());
+                self.new_name = "".to_owned();
+            }
+            Msg::StartRename(id) => {
+                let name = self
+                    .props
+                    .slots
+                    .iter()
+                    .find(|slot| slot.id == id)
+                    .map(|slot| slot.name.clone())
+                    .unwrap_or_default();
+                self.renaming = Some((id, name));
+            }
+            Msg::UpdateRename(name) => {
+                if let Some((_, renamed)) = self.renaming.as_mut() {
+                    *renamed = name;
+                }
+            }
+            Msg::FinishRename => {
+                if let Some((id, name)) = self.renaming.take() {
+                    if !name.trim().is_empty() {
+                        self.props.on_rename.emit((id, name.trim().to_owned()));
+                    }
+                }
+            }
+            Msg::CancelRename => {
+                self.renaming = None;
+            }
+            Msg::Load(id) => self.props.on_load.emit(id),
+            Msg::Delete(id) => {
+                if self
+                    .renaming
+                    .as_ref()
+                    .is_some_and(|(renaming_id, _)| *renaming_id == id)
+                {
+                    self.renaming = None;
+                }
+                self.props.on_delete.emit(id);
+            }
+            Msg::Export(id) => self.props.on_export.emit(id),
+            Msg::Close => self.props.on_close.emit(()),
+            Msg::Nope => return false,
+        }
+
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="overlay" onclick=self.link.callback(|_| Msg::Close)>
+                <div
+                    class="new-score-modal slot-manager-modal"
+                    onclick=self.link.callback(|event: MouseEvent| {
+                        event.stop_propagation();
+                        Msg::Nope
+                    })
+                >
+                    <h2>{"Saved Runs"}</h2>
+
+                    <form
+                        class="slot-save-form"
+                        onsubmit=self.link.callback(|event: Event| {
+                            event.prevent_default();
+                            Msg::Save
+                        })
+                    >
+                        <input
+                            class="slot-name-input"
+                            placeholder={if self.props.can_save { "Slot name" } else { "Pause the run to save it" }}
+                            disabled={!self.props.can_save}
+                            value={self.new_name.clone()}
+                            oninput=self.link.callback(|event: InputData| Msg::UpdateNewName(event.value))
+                        />
+                        <input
+                            type="submit"
+                            class="button submit-button"
+                            disabled={!self.props.can_save || self.new_name.trim().is_empty()}
+                            value={"Save"}
+                        />
+                    </form>
+
+                    {self.view_slots()}
+
+                    <div class="modal-buttons">
+                        <input
+                            type="button"
+                            value={"Close"}
+                            class="button ignore-button"
+                            onclick=self.link.callback(|_| Msg::Close)
+                        />
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl SlotManager {
+    fn view_slots(&self) -> Html {
+        if self.props.slots.is_empty() {
+            return html! { <p class="slot-empty">{"No saved runs yet."}</p> };
+        }
+
+        html! {
+            <ul class="slot-list">
+                {self.props.slots.iter().map(|slot| self.view_slot(slot)).collect::<Html>()}
+            </ul>
+        }
+    }
+
+    fn view_slot(&self, slot: &SaveSlot) -> Html {
+        let id = slot.id;
+        let name = match &self.renaming {
+            Some((renaming_id, renamed)) if *renaming_id == id => html! {
+                <form
+                    class="slot-rename-form"
+                    onsubmit=self.link.callback(|event: Event| {
+                        event.prevent_default();
+                        Msg::FinishRename
+                    })
+                >
+                    <input
+                        class="slot-name-input"
+                        value={renamed.clone()}
+                        oninput=self.link.callback(|event: InputData| Msg::UpdateRename(event.value))
+                    />
+                    <button type="submit">{"OK"}</button>
+                    <button type="button" onclick=self.link.callback(|_| Msg::CancelRename)>{"Cancel"}</button>
+                </form>
+            },
+            _ => html! {
+                <div class="slot-name">{slot.name.clone()}</div>
+            },
+        };
+        let saved_at = js_sys::Date::new(&slot.saved_at.into())
+            .to_locale_string("default", &js_sys::Object::new());
+
+        html! {
+            <li class="slot">
+                {name}
+                <div class="slot-details">
+                    {format!("{}, generation {}, {} edits", slot.seed_label, slot.generation, slot.modifications.len())}
+                </div>
+                <div class="slot-details">{String::from(saved_at)}</div>
+                <div class="slot-buttons">
+                    <button onclick=self.link.callback(move |_| Msg::Load(id))>{"Load"}</button>
+                    <button onclick=self.link.callback(move |_| Msg::StartRename(id))>{"Rename"}</button>
+                    <button onclick=self.link.callback(move |_| Msg::Export(id))>{"Export"}</button>
+                    <button class="slot-delete-button" onclick=self.link.callback(move |_| Msg::Delete(id))>{"Delete"}</button>
+                </div>
+            </li>
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use yew::prelude::*;
+
+use yewtil::NeqAssign;
+
+use crate::app::run_history::{personal_bests, run_length_histogram, PastRun};
+
+/// Buckets in the run length histogram.
+const HISTOGRAM_BUCKETS: usize = 10;
+/// Past runs listed for replay, newest first.
+const RECENT_RUNS: usize = 20;
+
+#[derive(Clone, PartialEq)]
+pub enum Msg {
+    Replay(usize),
+    Close,
+    Nope,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    #[prop_or_default]
+    pub runs: Vec<PastRun>,
+
+    /// Replays the run at this index of `runs`.
+    #[prop_or_default]
+    pub on_replay: Callback<usize>,
+    #[prop_or_default]
+    pub on_close: Callback<()>,
+}
+
+/// The player's own record: personal bests per seed, how long their runs
+/// last, and their recent runs to replay.
+pub struct StatsView {
+    props: Props,
+    link: ComponentLink<Self>,
+}
+
+impl Component for StatsView {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self { props, link }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Msg::Replay(index) => self.props.on_replay.emit(index),
+            Msg::Close => self.props.on_close.emit(()),
+            Msg::Nope => return false,
+        }
+
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="overlay" onclick=self.link.callback(|_| Msg::Close)>
+                <div
+                    class="new-score-modal stats-modal"
+                    onclick=self.link.callback(|event: MouseEvent| {
+                        event.stop_propagation();
+                        Msg::Nope
+                    })
+                >
+                    <h2>{"Your Runs"}</h2>
+
+                    {if self.props.runs.is_empty() {
+                        html! { <p>{"Finish a run and it will show up here."}</p> }
+                    } else {
+                        html! {
+                            <>
+                                {self.view_personal_bests()}
+                                {self.view_histogram()}
+                                {self.view_recent_runs()}
+                            </>
+                        }
+                    }}
+
+                    <div class="modal-buttons">
+                        <input
+                            type="button"
+                            value={"Close"}
+                            class="button ignore-button"
+                            onclick=self.link.callback(|_| Msg::Close)
+                        />
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl StatsView {
+    fn view_personal_bests(&self) -> Html {
+        html! {
+            <table class="stats-table">
+                <thead>
+                    <tr>
+                        <th>{"Seed"}</th>
+                        <th>{"Runs"}</th>
+                        <th>{"Best Life"}</th>
+                        <th>{"Best Death"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {personal_bests(&self.props.runs).into_iter().map(|best| html! {
+                        <tr>
+                            <td>{best.seed_label.clone()}<div class="stats-settings">{best.settings()}</div></td>
+                            <td>{best.run_count}</td>
+                            <td>{format!("{} steps, {} active", best.life.step_count, best.life.active_count)}</td>
+                            <td>{format!("{} active, {} steps", best.death.active_count, best.death.step_count)}</td>
+                        </tr>
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        }
+    }
+
+    fn view_histogram(&self) -> Html {
+        let buckets = run_length_histogram(&self.props.runs, HISTOGRAM_BUCKETS);
+        let most = buckets
+            .iter()
+            .map(|bucket| bucket.count)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        html! {
+            <div class="stats-histogram">
+                <div class="metric-label">{"Run Lengths"}</div>
+                <div class="histogram-bars">
+                    {buckets.into_iter().map(|bucket| html! {
+                        <div
+                            class="histogram-bar"
+                            style=format!("height: {}%", bucket.count * 100 / most)
+                            title=format!("{} to {} steps: {} runs", bucket.start, bucket.end, bucket.count)
+                        ></div>
+                    }).collect::<Html>()}
+                </div>
+            </div>
+        }
+    }
+
+    fn view_recent_runs(&self) -> Html {
+        html! {
+            <ul class="recent-runs">
+                {self.props.runs.iter().enumerate().rev().take(RECENT_RUNS).map(|(index, run)| html! {
+                    <li class="recent-run">
+                        <span>
+                            {format!(
+                                "{}: {} steps, {} active, {} edits ({})",
+                                run.seed_label,
+                                run.step_count,
+                                run.active_count,
+                                run.modifications.len(),
+                                run.end_reason.label()
+                            )}
+                        </span>
+                        <button onclick=self.link.callback(move |_| Msg::Replay(index))>{"Replay"}</button>
+                    </li>
+                }).collect::<Html>()}
+            </ul>
+        }
+    }
+}
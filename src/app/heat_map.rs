@@ -0,0 +1,120 @@
+//! How often each cellule changed state during a run, to show where the
+//! board was busiest.
+//!
+//! Like the cellule ages, counts are kept by position on the plane and
+//! boards are passed in as a window: the position of their first cellule,
+//! their width and their alive flags.
+
+use std::collections::{HashMap, HashSet};
+
+use super::board_window::BoardWindow;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeatMap {
+    counts: HashMap<(i64, i64), u32>,
+    previous: HashSet<(i64, i64)>,
+    previous_window: BoardWindow,
+}
+
+impl HeatMap {
+    /// Starts counting over from the board a run starts on.
+    pub fn start(&mut self, origin: (i64, i64), width: usize, board: &[bool]) {
+        self.clear();
+        self.amend(origin, width, board);
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.previous.clear();
+        self.previous_window = BoardWindow::default();
+    }
+
+    /// Counts the cellules that were born or died since the last board.
+    /// Only cellules in both windows are compared: on the infinite plane a
+    /// cellule moving in or out of view has not changed state.
+    pub fn record(&mut self, origin: (i64, i64), width: usize, board: &[bool]) {
+        let window = BoardWindow::of_board(origin, width, board);
+        let current = window
+            .alive_positions(board)
+            .collect::<HashSet<(i64, i64)>>();
+        for position in current.symmetric_difference(&self.previous) {
+            if window.contains(*position) && self.previous_window.contains(*position) {
+                *self.counts.entry(*position).or_insert(0) += 1;
+            }
+        }
+        self.previous = current;
+        self.previous_window = window;
+    }
+
+    /// Takes in a board without counting its changes, for edits and jumps.
+    pub fn amend(&mut self, origin: (i64, i64), width: usize, board: &[bool]) {
+        let window = BoardWindow::of_board(origin, width, board);
+        self.previous = window.alive_positions(board).collect();
+        self.previous_window = window;
+    }
+
+    /// The count of every cellule in a window.
+    pub fn counts_in(&self, origin: (i64, i64), width: usize, height: usize) -> Vec<u32> {
+        BoardWindow::new(origin, width, height)
+            .positions()
+            .map(|position| self.counts.get(&position).copied().unwrap_or(0))
+            .collect()
+    }
+
+    /// The counts of a window as CSV, one line per row of cellules.
+    pub fn to_csv(&self, origin: (i64, i64), width: usize, height: usize) -> String {
+        self.counts_in(origin, width, height)
+            .chunks(width.max(1))
+            .map(|row| {
+                row.iter()
+                    .map(|count| count.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            })
+            .map(|line| line + "\n")
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_counts_state_changes() {
+        let mut heat_map = HeatMap::default();
+        heat_map.start((0, 0), 2, &[true, false, false, false]);
+        heat_map.record((0, 0), 2, &[false, true, false, false]);
+        // Edits are not part of the run.
+        heat_map.amend((0, 0), 2, &[false, true, false, true]);
+        heat_map.record((0, 0), 2, &[true, true, false, false]);
+        heat_map.record((-1, 0), 3, &[false, true, true, false, false, false]);
+
+        assert_eq!(heat_map.counts_in((0, 0), 2, 2), vec![2, 1, 0, 1]);
+
+        heat_map.clear();
+        assert_eq!(heat_map.counts_in((0, 0), 2, 2), vec![0; 4]);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut heat_map = HeatMap::default();
+        heat_map.start((0, 0), 3, &[false; 6]);
+        heat_map.record((0, 0), 3, &[true, false, false, false, false, true]);
+        heat_map.record((0, 0), 3, &[false, false, false, false, false, true]);
+
+        assert_eq!(heat_map.to_csv((0, 0), 3, 2), "2,0,0\n0,0,1\n");
+    }
+
+    #[test]
+    fn test_cellules_moving_out_of_view_did_not_change() {
+        let mut heat_map = HeatMap::default();
+        heat_map.start((0, 0), 2, &[true, false, false, true]);
+        // The window moves one to the right: (0, 0) leaves it, still alive,
+        // and (2, 0) comes into view alive. Only (1, 1) died.
+        heat_map.record((1, 0), 2, &[false, true, false, false]);
+
+        assert_eq!(heat_map.counts_in((0, 0), 3, 2), vec![0, 0, 0, 0, 1, 0]);
+    }
+}
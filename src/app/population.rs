@@ -0,0 +1,199 @@
+//! The population of the current run, generation by generation, for the
+//! chart under the grid.
+
+/// One generation of a run.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GenerationStats {
+    pub generation: i32,
+    pub population: usize,
+    pub births: usize,
+    pub deaths: usize,
+    /// Reached by jumping ahead rather than by a step: its births and
+    /// deaths are unknown and the chart does not join it to the one before.
+    pub jumped: bool,
+}
+
+/// The generations recorded so far, and the board of the last one to count
+/// the next births and deaths against.
+#[derive(Clone, Debug, Default)]
+pub struct PopulationHistory {
+    samples: Vec<GenerationStats>,
+    board: Vec<bool>,
+}
+
+impl PopulationHistory {
+    /// Starts over from the board a run starts on.
+    pub fn start(&mut self, population: usize, board: Vec<bool>) {
+        self.samples = vec![GenerationStats {
+            generation: 0,
+            population,
+            births: 0,
+            deaths: 0,
+            jumped: false,
+        }];
+        self.board = board;
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.board.clear();
+    }
+
+    /// Records a generation. Births and deaths are counted against the last
+    /// recorded board unless the engine counted them itself.
+    pub fn record(
+        &mut self,
+        generation: i32,
+        population: usize,
+        board: Vec<bool>,
+        changes: Option<(usize, usize)>,
+    ) {
+        let (births, deaths) = changes.unwrap_or_else(|| count_changes(&self.board, &board));
+
+        self.samples.push(GenerationStats {
+            generation,
+            population,
+            births,
+            deaths,
+            jumped: false,
+        });
+        self.board = board;
+    }
+
+    /// Records the generation a jump landed on, which starts a new segment
+    /// of the chart.
+    pub fn record_jump(&mut self, generation: i32, population: usize, board: Vec<bool>) {
+        self.samples.push(GenerationStats {
+            generation,
+            population,
+            births: 0,
+            deaths: 0,
+            jumped: true,
+        });
+        self.board = board;
+    }
+
+    /// Takes in edits made to the last recorded generation, which are
+    /// neither births nor deaths.
+    pub fn amend(&mut self, population: usize, board: Vec<bool>) {
+        if let Some(last) = self.samples.last_mut() {
+            last.population = population;
+        }
+        self.board = board;
+    }
+
+    pub fn samples(&self) -> &[GenerationStats] {
+        &self.samples
+    }
+}
+
+/// The recorded generation closest to `generation`.
+pub fn nearest_sample(samples: &[GenerationStats], generation: i32) -> Option<&GenerationStats> {
+    let index = samples.partition_point(|sample| sample.generation < generation);
+    let after = samples.get(index);
+    let before = index.checked_sub(1).and_then(|index| samples.get(index));
+
+    match (before, after) {
+        (Some(before), Some(after)) => {
+            if generation - before.generation <= after.generation - generation {
+                Some(before)
+            } else {
+                Some(after)
+            }
+        }
+        (before, after) => before.or(after),
+    }
+}
+
+/// Cellules that came alive and cellules that died between two boards.
+fn count_changes(previous: &[bool], current: &[bool]) -> (usize, usize) {
+    if previous.len() != current.len() {
+        return (0, 0);
+    }
+
+    previous
+        .iter()
+        .zip(current)
+        .fold((0, 0), |(births, deaths), (was_alive, is_alive)| {
+            match (was_alive, is_alive) {
+                (false, true) => (births + 1, deaths),
+                (true, false) => (births, deaths + 1),
+                _ => (births, deaths),
+            }
+        })
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_records_births_and_deaths() {
+        let mut history = PopulationHistory::default();
+        history.start(2, vec![true, true, false, false]);
+        history.record(1, 2, vec![false, true, true, false], None);
+        history.amend(3, vec![true, true, true, false]);
+        history.record(2, 1, vec![false, false, false, true], None);
+        history.record(3, 40, vec![false; 4], Some((45, 6)));
+        history.record_jump(500, 2, vec![true, false, false, true]);
+        history.record(501, 1, vec![true, false, false, false], None);
+
+        let changes = history
+            .samples()
+            .iter()
+            .map(|sample| {
+                (
+                    sample.generation,
+                    sample.population,
+                    sample.births,
+                    sample.deaths,
+                    sample.jumped,
+                )
+            })
+            .collect::<Vec<(i32, usize, usize, usize, bool)>>();
+        assert_eq!(
+            changes,
+            vec![
+                (0, 2, 0, 0, false),
+                (1, 3, 1, 1, false),
+                (2, 1, 1, 3, false),
+                (3, 40, 45, 6, false),
+                (500, 2, 0, 0, true),
+                (501, 1, 0, 1, false),
+            ]
+        );
+
+        history.clear();
+        assert!(history.samples().is_empty());
+    }
+
+    #[test]
+    fn test_nearest_sample() {
+        let samples = [0, 1, 2, 500, 501]
+            .iter()
+            .map(|&generation| GenerationStats {
+                generation,
+                population: 0,
+                births: 0,
+                deaths: 0,
+                jumped: false,
+            })
+            .collect::<Vec<GenerationStats>>();
+
+        let cases = vec![
+            // (generation, nearest)
+            (-5, Some(0)),
+            (1, Some(1)),
+            (250, Some(2)),
+            (252, Some(500)),
+            (9000, Some(501)),
+        ];
+
+        for (generation, expected) in cases {
+            let nearest = nearest_sample(&samples, generation).map(|sample| sample.generation);
+            assert_eq!(nearest, expected, "generation {}", generation);
+        }
+        assert_eq!(nearest_sample(&[], 3), None);
+    }
+}
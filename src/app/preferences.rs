@@ -0,0 +1,97 @@
+//! What the player chose last time, kept across sessions.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::app::components::grid::ColorMode;
+use crate::app::storage::{unversioned, Schema, VersionedStorage};
+
+const PREFERENCES_KEY: &str = "yew.gameofdeath.preferences";
+/// Where the intro modal was remembered before there were preferences.
+const INTRO_MODAL_KEY: &str = "yew.gameofdeath.showing_intro_modal";
+
+const PREFERENCES_SCHEMA: Schema = Schema {
+    version: 1,
+    migrations: &[unversioned],
+};
+const INTRO_MODAL_SCHEMA: Schema = Schema {
+    version: 1,
+    migrations: &[unversioned],
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Generations per second.
+    pub rate: f64,
+    /// The label of the last seed played, empty for the first seed.
+    pub seed_label: String,
+    /// The name last entered for a score.
+    pub user_name: String,
+    /// The name of the chosen theme, `None` to follow the system.
+    pub theme: Option<String>,
+    /// What the colors of alive cellules show.
+    pub color_mode: ColorMode,
+    /// The name of the palette alive cellules are colored with, `None` for
+    /// the theme's.
+    pub palette: Option<String>,
+    pub showing_intro_modal: bool,
+}
+
+impl Default for Preferences {
+    fn default() -> Preferences {
+        Preferences {
+            rate: 60.0,
+            seed_label: "".to_owned(),
+            user_name: "".to_owned(),
+            theme: None,
+            color_mode: ColorMode::Position,
+            palette: None,
+            showing_intro_modal: true,
+        }
+    }
+}
+
+impl Preferences {
+    /// The stored preferences. The first time, the intro modal setting is
+    /// taken over from its old key.
+    pub fn restore(storage: &mut VersionedStorage) -> Preferences {
+        if let Some(preferences) = storage.restore(PREFERENCES_KEY, &PREFERENCES_SCHEMA) {
+            return preferences;
+        }
+
+        let preferences = Preferences {
+            showing_intro_modal: storage
+                .restore(INTRO_MODAL_KEY, &INTRO_MODAL_SCHEMA)
+                .unwrap_or(true),
+            ..Preferences::default()
+        };
+        preferences.store(storage);
+        storage.remove(INTRO_MODAL_KEY);
+        preferences
+    }
+
+    pub fn store(&self, storage: &mut VersionedStorage) {
+        storage.store(PREFERENCES_KEY, &PREFERENCES_SCHEMA, self);
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_fall_back_to_defaults() {
+        let preferences: Preferences =
+            serde_json::from_str(r#"{"rate": 30.0, "user_name": "ABCD"}"#).unwrap();
+
+        assert_eq!(
+            preferences,
+            Preferences {
+                rate: 30.0,
+                user_name: "ABCD".to_owned(),
+                ..Preferences::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Every finished run, kept locally so players can see their own record
+//! whether or not they made the global leaderboards.
+
+use serde_derive::{Deserialize, Serialize};
+
+use cellule_life_engine::census::CensusEntry;
+use cellule_life_engine::geometry::Geometry;
+use cellule_life_engine::rules::Rule;
+use cellule_life_engine::Topology;
+use cellule_life_schema::ranking::{Edits, Leaderboard, LeaderboardKey, Score};
+use cellule_life_schema::GridModification;
+
+use crate::app::storage::{unversioned, Schema, VersionedStorage};
+
+/// Older runs are dropped once there are more than this.
+pub const MAX_RUNS: usize = 1000;
+
+const RUN_HISTORY_KEY: &str = "yew.gameofdeath.run_history";
+
+const RUN_HISTORY_SCHEMA: Schema = Schema {
+    version: 1,
+    migrations: &[unversioned],
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EndReason {
+    /// No cellule was left alive.
+    Extinct,
+    /// The board repeated an earlier generation.
+    EndlessLoop,
+    /// The run reached `MAX_STEP_COUNT`.
+    StepLimit,
+}
+
+impl EndReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EndReason::Extinct => "Extinct",
+            EndReason::EndlessLoop => "Loop",
+            EndReason::StepLimit => "Step limit",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PastRun {
+    /// When the run ended, in milliseconds since the epoch.
+    pub finished_at: f64,
+    pub seed_label: String,
+    pub topology: Topology,
+    pub rule: Rule,
+    pub geometry: Geometry,
+    pub edit_budget: Option<usize>,
+    pub step_count: i32,
+    pub active_count: i32,
+    pub end_reason: EndReason,
+    pub modifications: Vec<GridModification>,
+    /// The objects left on the board, for runs of plain Life.
+    #[serde(default)]
+    pub census: Vec<CensusEntry>,
+}
+
+impl PastRun {
+    pub fn score(&self) -> Score {
+        Score::new(self.step_count, self.active_count)
+    }
+
+    /// The leaderboard the run would have competed on.
+    pub fn leaderboard_key(&self) -> LeaderboardKey {
+        LeaderboardKey::new(
+            &self.seed_label,
+            self.edit_budget,
+            self.modifications.len(),
+            self.topology,
+            &self.rule,
+            self.geometry,
+        )
+    }
+}
+
+/// The best Life and Death runs played on one leaderboard.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PersonalBest {
+    pub key: LeaderboardKey,
+    pub seed_label: String,
+    pub run_count: usize,
+    pub life: Score,
+    pub death: Score,
+}
+
+/// How many runs had a step count in `start..=end`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bucket {
+    pub start: i32,
+    pub end: i32,
+    pub count: usize,
+}
+
+/// The stored runs, oldest first.
+pub fn restore_runs(storage: &mut VersionedStorage) -> Vec<PastRun> {
+    storage
+        .restore(RUN_HISTORY_KEY, &RUN_HISTORY_SCHEMA)
+        .unwrap_or_default()
+}
+
+/// Adds `run`, dropping the oldest runs past `MAX_RUNS`.
+pub fn record_run(storage: &mut VersionedStorage, runs: &mut Vec<PastRun>, run: PastRun) {
+    runs.push(run);
+    if runs.len() > MAX_RUNS {
+        let excess = runs.len() - MAX_RUNS;
+        runs.drain(..excess);
+    }
+    storage.store(RUN_HISTORY_KEY, &RUN_HISTORY_SCHEMA, runs);
+}
+
+impl PersonalBest {
+    /// What sets the leaderboard apart from others on the same seed, empty
+    /// for the Daily Challenge and unedited Life on the bounded square board.
+    pub fn settings(&self) -> String {
+        let (edits, topology, rule, geometry) = match &self.key {
+            LeaderboardKey::Seed {
+                edits,
+                topology,
+                rule,
+                geometry,
+                ..
+            } => (edits, topology, rule, geometry),
+            LeaderboardKey::Daily(_) => return "".to_owned(),
+        };
+
+        let mut settings = vec![];
+        match edits {
+            Edits::Count(0) => {}
+            Edits::Count(1) => settings.push("1 edit".to_owned()),
+            Edits::Count(count) => settings.push(format!("{} edits", count)),
+            Edits::Budget(budget) => settings.push(format!("{} edit budget", budget)),
+        }
+        if *topology == Topology::Infinite {
+            settings.push("Infinite".to_owned());
+        }
+        if !rule.is_conway() {
+            settings.push(rule.to_string());
+        }
+        if *geometry != Geometry::Square {
+            settings.push(geometry.label().to_owned());
+        }
+        settings.join(", ")
+    }
+}
+
+/// Personal bests per leaderboard, in the order they were first played.
+pub fn personal_bests(runs: &[PastRun]) -> Vec<PersonalBest> {
+    let mut bests: Vec<PersonalBest> = vec![];
+
+    for run in runs {
+        let score = run.score();
+        let key = run.leaderboard_key();
+        match bests.iter_mut().find(|best| best.key == key) {
+            Some(best) => {
+                best.run_count += 1;
+                if Leaderboard::Life.compare(&score, &best.life).is_lt() {
+                    best.life = score;
+                }
+                if Leaderboard::Death.compare(&score, &best.death).is_lt() {
+                    best.death = score;
+                }
+            }
+            None => bests.push(PersonalBest {
+                key,
+                seed_label: run.seed_label.clone(),
+                run_count: 1,
+                life: score,
+                death: score,
+            }),
+        }
+    }
+
+    bests
+}
+
+/// Run lengths in `bucket_count` buckets of equal width, wide enough to
+/// hold the longest run.
+pub fn run_length_histogram(runs: &[PastRun], bucket_count: usize) -> Vec<Bucket> {
+    let longest = match runs.iter().map(|run| run.step_count.max(0)).max() {
+        Some(longest) if bucket_count > 0 => longest,
+        _ => return vec![],
+    };
+    let width = longest / bucket_count as i32 + 1;
+
+    let mut buckets = (0..bucket_count as i32)
+        .map(|index| Bucket {
+            start: index * width,
+            end: (index + 1) * width - 1,
+            count: 0,
+        })
+        .collect::<Vec<Bucket>>();
+    for run in runs {
+        let index = (run.step_count.max(0) / width) as usize;
+        buckets[index.min(bucket_count - 1)].count += 1;
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    fn run(seed_label: &str, step_count: i32, active_count: i32) -> PastRun {
+        PastRun {
+            finished_at: 0.0,
+            seed_label: seed_label.to_owned(),
+            topology: Topology::Bounded,
+            rule: Rule::conway(),
+            geometry: Geometry::Square,
+            edit_budget: None,
+            step_count,
+            active_count,
+            end_reason: EndReason::EndlessLoop,
+            modifications: vec![],
+            census: vec![],
+        }
+    }
+
+    #[test]
+    fn test_personal_bests_per_leaderboard() {
+        let mut infinite = run("Glider", 500, 1);
+        infinite.topology = Topology::Infinite;
+        let mut edited = run("Glider", 900, 30);
+        edited.modifications = vec![GridModification {
+            step_index: 0,
+            grid_index: 4,
+        }];
+        let runs = vec![
+            run("Glider", 100, 5),
+            run("Blinker", 20, 3),
+            run("Glider", 300, 9),
+            infinite,
+            run("Glider", 40, 0),
+            edited,
+            run("Glider", 300, 12),
+        ];
+
+        let expected = vec![
+            // (seed, settings, run count, best Life, best Death)
+            ("Glider", "", 4, Score::new(300, 12), Score::new(40, 0)),
+            ("Blinker", "", 1, Score::new(20, 3), Score::new(20, 3)),
+            (
+                "Glider",
+                "Infinite",
+                1,
+                Score::new(500, 1),
+                Score::new(500, 1),
+            ),
+            (
+                "Glider",
+                "1 edit",
+                1,
+                Score::new(900, 30),
+                Score::new(900, 30),
+            ),
+        ];
+
+        let bests = personal_bests(&runs);
+        assert_eq!(bests.len(), expected.len());
+        for (best, (seed_label, settings, run_count, life, death)) in bests.iter().zip(expected) {
+            assert_eq!(best.seed_label, seed_label);
+            assert_eq!(best.settings(), settings);
+            assert_eq!(
+                (best.run_count, best.life, best.death),
+                (run_count, life, death)
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_length_histogram() {
+        let runs = vec![
+            run("Glider", 0, 0),
+            run("Glider", 5, 0),
+            run("Glider", 9, 0),
+            run("Glider", 10, 0),
+            run("Glider", 19, 0),
+        ];
+
+        let cases = vec![
+            // (bucket count, expected (start, end, count))
+            (1, vec![(0, 19, 5)]),
+            (2, vec![(0, 9, 3), (10, 19, 2)]),
+            (4, vec![(0, 4, 1), (5, 9, 2), (10, 14, 1), (15, 19, 1)]),
+        ];
+
+        for (bucket_count, expected) in cases {
+            let buckets = run_length_histogram(&runs, bucket_count)
+                .into_iter()
+                .map(|bucket| (bucket.start, bucket.end, bucket.count))
+                .collect::<Vec<(i32, i32, usize)>>();
+            assert_eq!(buckets, expected, "{} buckets", bucket_count);
+        }
+
+        assert!(run_length_histogram(&[], 10).is_empty());
+        assert!(run_length_histogram(&runs, 0).is_empty());
+    }
+}
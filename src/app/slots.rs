@@ -0,0 +1,157 @@
+//! Named save slots in local storage.
+//!
+//! A slot records how to rebuild a board rather than the board itself: the
+//! seed, the settings, the edits and how far the run had got. Loading a slot
+//! replays the run up to that generation, edits included.
+//!
+//! Every slot has its own key, next to an index key that lists the slot ids
+//! in order, so renaming or deleting a slot only rewrites that slot. The keys
+//! carry `SLOT_VERSION`, and slots also store it so an exported slot can be
+//! told apart from one written by a later build.
+
+use serde_derive::{Deserialize, Serialize};
+
+use cellule_life_engine::geometry::Geometry;
+use cellule_life_engine::rules::Rule;
+use cellule_life_engine::Topology;
+use cellule_life_schema::GridModification;
+
+use crate::app::storage::{unversioned, Schema, VersionedStorage};
+
+/// The version of the slot format, part of every slot key.
+pub const SLOT_VERSION: u32 = 1;
+
+const SLOT_INDEX_KEY: &str = "yew.gameofdeath.slots.v1";
+
+const SLOT_INDEX_SCHEMA: Schema = Schema {
+    version: 1,
+    migrations: &[unversioned],
+};
+const SLOT_SCHEMA: Schema = Schema {
+    version: SLOT_VERSION,
+    migrations: &[unversioned],
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SaveSlot {
+    pub version: u32,
+    pub id: u32,
+    pub name: String,
+    /// When the slot was last written, in milliseconds since the epoch.
+    pub saved_at: f64,
+    pub seed_label: String,
+    pub modifications: Vec<GridModification>,
+    pub cellules_width: usize,
+    pub cellules_height: usize,
+    pub topology: Topology,
+    pub rule: Rule,
+    pub geometry: Geometry,
+    pub edit_budget: Option<usize>,
+    /// The generation the run was saved at, 0 before it started.
+    pub generation: i32,
+}
+
+impl SaveSlot {
+    /// The slot as a JSON file to share or keep outside the browser.
+    pub fn export(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// A file name for the exported slot.
+    pub fn export_file_name(&self) -> String {
+        let name = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>();
+
+        format!(
+            "cellule-life-{}.json",
+            name.trim_matches('-').to_lowercase()
+        )
+    }
+}
+
+/// The saved slots, in the order they were first saved.
+pub fn restore_slots(storage: &mut VersionedStorage) -> Vec<SaveSlot> {
+    let ids: Vec<u32> = storage
+        .restore(SLOT_INDEX_KEY, &SLOT_INDEX_SCHEMA)
+        .unwrap_or_default();
+
+    ids.into_iter()
+        .filter_map(|id| storage.restore(&slot_key(id), &SLOT_SCHEMA))
+        .collect()
+}
+
+/// Writes `slot`, and the index listing `slots`.
+pub fn store_slot(storage: &mut VersionedStorage, slots: &[SaveSlot], slot: &SaveSlot) {
+    storage.store(&slot_key(slot.id), &SLOT_SCHEMA, slot);
+    storage.store(SLOT_INDEX_KEY, &SLOT_INDEX_SCHEMA, &slot_ids(slots));
+}
+
+/// Removes the slot `id`. `slots` are the slots that remain.
+pub fn remove_slot(storage: &mut VersionedStorage, slots: &[SaveSlot], id: u32) {
+    storage.remove(&slot_key(id));
+    storage.store(SLOT_INDEX_KEY, &SLOT_INDEX_SCHEMA, &slot_ids(slots));
+}
+
+/// An id no slot uses. Ids are never reused while a later slot exists.
+pub fn next_slot_id(slots: &[SaveSlot]) -> u32 {
+    slots.iter().map(|slot| slot.id + 1).max().unwrap_or(0)
+}
+
+fn slot_ids(slots: &[SaveSlot]) -> Vec<u32> {
+    slots.iter().map(|slot| slot.id).collect()
+}
+
+fn slot_key(id: u32) -> String {
+    format!("{}.{}", SLOT_INDEX_KEY, id)
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    fn slot(id: u32, name: &str) -> SaveSlot {
+        SaveSlot {
+            version: SLOT_VERSION,
+            id,
+            name: name.to_owned(),
+            saved_at: 0.0,
+            seed_label: "Glider".to_owned(),
+            modifications: vec![GridModification {
+                grid_index: 60,
+                step_index: 12,
+            }],
+            cellules_width: 50,
+            cellules_height: 40,
+            topology: Topology::Bounded,
+            rule: Rule::conway(),
+            geometry: Geometry::Square,
+            edit_budget: Some(5),
+            generation: 30,
+        }
+    }
+
+    #[test]
+    fn test_slot_keys_are_versioned() {
+        assert_eq!(slot_key(3), "yew.gameofdeath.slots.v1.3");
+        assert!(SLOT_INDEX_KEY.ends_with(&format!(".v{}", SLOT_VERSION)));
+    }
+
+    #[test]
+    fn test_next_slot_id() {
+        assert_eq!(next_slot_id(&[]), 0);
+        assert_eq!(next_slot_id(&[slot(0, "a"), slot(4, "b"), slot(2, "c")]), 5);
+    }
+
+    #[test]
+    fn test_export_round_trips() {
+        let saved = slot(7, "My Run #2");
+        let exported: SaveSlot = serde_json::from_str(&saved.export()).unwrap();
+
+        assert_eq!(exported, saved);
+        assert_eq!(saved.export_file_name(), "cellule-life-my-run--2.json");
+    }
+}
@@ -0,0 +1,262 @@
+//! Local storage with a schema version on every value.
+//!
+//! Each value is written as `{"version": N, "data": ...}`. Reading a value
+//! from an older version runs it through the migrations of its `Schema` and
+//! writes the result back. Values written before storage was versioned are
+//! read as version 0.
+//!
+//! A value that can't be read, whether it is corrupt, can't be migrated or
+//! comes from a newer build, is never overwritten. It is moved to a
+//! quarantine key under `QUARANTINE_PREFIX` first, and the caller falls back
+//! to its default.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use yew::format::Text;
+use yew::services::storage::{Area, StorageService};
+
+/// Where values that could not be read are kept.
+pub const QUARANTINE_PREFIX: &str = "yew.gameofdeath.quarantine.";
+
+/// Turns data of one version into data of the next.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// The shape of one kind of stored value. `migrations[n]` upgrades version
+/// `n` to `n + 1`, so there is one migration per version before `version`.
+pub struct Schema {
+    pub version: u32,
+    pub migrations: &'static [Migration],
+}
+
+/// Why a stored value could not be read.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StorageError {
+    /// The value is not JSON.
+    Corrupt(String),
+    /// A newer build wrote the value.
+    NewerVersion(u32),
+    /// A migration from `from` to the next version failed.
+    Migration { from: u32, reason: String },
+    /// The migrated value does not fit the type it is read as.
+    Decode(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Corrupt(reason) => write!(formatter, "corrupt value ({})", reason),
+            StorageError::NewerVersion(version) => {
+                write!(formatter, "written by a newer version ({})", version)
+            }
+            StorageError::Migration { from, reason } => {
+                write!(
+                    formatter,
+                    "could not migrate from version {} ({})",
+                    from, reason
+                )
+            }
+            StorageError::Decode(reason) => write!(formatter, "unexpected value ({})", reason),
+        }
+    }
+}
+
+/// Values written before storage was versioned are already in the shape
+/// version 1 expects.
+pub fn unversioned(value: Value) -> Result<Value, String> {
+    Ok(value)
+}
+
+pub struct VersionedStorage {
+    storage: StorageService,
+}
+
+impl VersionedStorage {
+    pub fn new(area: Area) -> VersionedStorage {
+        VersionedStorage {
+            storage: StorageService::new(area).unwrap(),
+        }
+    }
+
+    /// The value under `key`, brought up to `schema.version`. `None` when
+    /// there is no value, or when it could not be read and was quarantined.
+    pub fn restore<T: DeserializeOwned>(&mut self, key: &str, schema: &Schema) -> Option<T> {
+        let raw: Text = self.storage.restore(key);
+        let raw = raw.ok()?;
+
+        let restored = migrate(&raw, schema).and_then(|(data, version)| {
+            let value = serde_json::from_value::<T>(data.clone())
+                .map_err(|error| StorageError::Decode(error.to_string()))?;
+            Ok((value, data, version))
+        });
+
+        match restored {
+            Ok((value, data, version)) => {
+                if version != schema.version {
+                    self.store_raw(key, &envelope(schema.version, data));
+                }
+                Some(value)
+            }
+            Err(error) => {
+                warn!("quarantined {}: {}", key, error);
+                self.quarantine(key, raw);
+                None
+            }
+        }
+    }
+
+    pub fn store<T: Serialize>(&mut self, key: &str, schema: &Schema, value: &T) {
+        match serde_json::to_value(value) {
+            Ok(data) => self.store_raw(key, &envelope(schema.version, data)),
+            Err(error) => warn!("could not store {}: {}", key, error),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.storage.remove(key);
+    }
+
+    fn store_raw(&mut self, key: &str, value: &Value) {
+        self.storage.store(key, Ok(value.to_string()));
+    }
+
+    /// Moves the raw value out of the way. Each quarantine gets its own key,
+    /// so an earlier one is never overwritten either.
+    fn quarantine(&mut self, key: &str, raw: String) {
+        let backup_key = format!(
+            "{}{}.{}",
+            QUARANTINE_PREFIX,
+            key,
+            js_sys::Date::now() as u64
+        );
+        self.storage.store(&backup_key, Ok(raw));
+        self.storage.remove(key);
+    }
+}
+
+fn envelope(version: u32, data: Value) -> Value {
+    json!({ "version": version, "data": data })
+}
+
+/// Reads a raw stored value and migrates it to `schema.version`. Returns the
+/// data and the version it was stored as.
+fn migrate(raw: &str, schema: &Schema) -> Result<(Value, u32), StorageError> {
+    let value: Value =
+        serde_json::from_str(raw).map_err(|error| StorageError::Corrupt(error.to_string()))?;
+
+    let (stored_version, mut data) = match value {
+        Value::Object(mut object) if object.len() == 2 && object.contains_key("data") => {
+            let version = object
+                .get("version")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| StorageError::Corrupt("version is not a number".to_owned()))?;
+            (version as u32, object.remove("data").unwrap_or(Value::Null))
+        }
+        unversioned => (0, unversioned),
+    };
+
+    if stored_version > schema.version {
+        return Err(StorageError::NewerVersion(stored_version));
+    }
+
+    for from in stored_version..schema.version {
+        let migration =
+            schema
+                .migrations
+                .get(from as usize)
+                .ok_or_else(|| StorageError::Migration {
+                    from,
+                    reason: "no migration".to_owned(),
+                })?;
+        data = migration(data).map_err(|reason| StorageError::Migration { from, reason })?;
+    }
+
+    Ok((data, stored_version))
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    fn double(value: Value) -> Result<Value, String> {
+        match value.as_i64() {
+            Some(number) => Ok(json!(number * 2)),
+            None => Err("not a number".to_owned()),
+        }
+    }
+
+    const SCHEMA: Schema = Schema {
+        version: 2,
+        migrations: &[unversioned, double],
+    };
+
+    #[test]
+    fn test_migrate() {
+        let cases = vec![
+            // (raw, expected)
+            (r#"{"version": 2, "data": 5}"#, Ok((json!(5), 2))),
+            (r#"{"version": 1, "data": 5}"#, Ok((json!(10), 1))),
+            ("5", Ok((json!(10), 0))),
+            (
+                "false",
+                Err(StorageError::Migration {
+                    from: 1,
+                    reason: "not a number".to_owned(),
+                }),
+            ),
+            (
+                r#"{"version": 3, "data": 5}"#,
+                Err(StorageError::NewerVersion(3)),
+            ),
+            (
+                r#"{"version": "two", "data": 5}"#,
+                Err(StorageError::Corrupt("version is not a number".to_owned())),
+            ),
+        ];
+
+        for (raw, expected) in cases {
+            assert_eq!(migrate(raw, &SCHEMA), expected, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_unversioned_objects_are_not_mistaken_for_envelopes() {
+        let raw = r#"{"data": 1, "version": 1, "other": 2}"#;
+        let schema = Schema {
+            version: 1,
+            migrations: &[unversioned],
+        };
+
+        assert_eq!(
+            migrate(raw, &schema),
+            Ok((serde_json::from_str(raw).unwrap(), 0))
+        );
+    }
+
+    #[test]
+    fn test_corrupt_values_are_rejected() {
+        assert!(matches!(
+            migrate("{not json", &SCHEMA),
+            Err(StorageError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_migrations_are_rejected() {
+        let schema = Schema {
+            version: 3,
+            migrations: &[unversioned],
+        };
+
+        assert_eq!(
+            migrate("1", &schema),
+            Err(StorageError::Migration {
+                from: 1,
+                reason: "no migration".to_owned()
+            })
+        );
+    }
+}
@@ -4,7 +4,7 @@ use log::*;
 #[allow(unused_imports)]
 use simplelog::*;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Color {
   pub red: f32,
 
@@ -13,41 +13,290 @@ pub struct Color {
   pub blue: f32,
 }
 
+/// Where the colors between two stops are mixed. RGB mixes straight through
+/// the channels, HSL goes around the color wheel the short way, and OKLab
+/// keeps the lightness even along the gradient.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColorSpace {
+  Rgb,
+  Hsl,
+  Oklab,
+}
+
+/// A named gradient the cellules can be colored with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Palette {
+  pub name: &'static str,
+  pub stops: &'static [Color],
+  pub color_space: ColorSpace,
+}
+
+impl Palette {
+  /// The palette called `name`, or the first one if there is none.
+  pub fn named(name: &str) -> Palette {
+    PALETTES
+      .iter()
+      .find(|palette| palette.name == name)
+      .copied()
+      .unwrap_or(PALETTES[0])
+  }
+
+  pub fn gradient(&self) -> GradientManager {
+    GradientManager::with_stops(self.stops.to_vec(), self.color_space)
+  }
+}
+
+/// A color from its channels, for constants.
+pub const fn rgb(red: f32, green: f32, blue: f32) -> Color {
+  Color { red, green, blue }
+}
+
+/// The palettes on offer. The first is the original orange to blue.
+pub const PALETTES: [Palette; 6] = [
+  Palette {
+    name: "Classic",
+    stops: &[rgb(246.0, 157.0, 60.0), rgb(63.0, 135.0, 166.0)],
+    color_space: ColorSpace::Rgb,
+  },
+  Palette {
+    name: "Viridis",
+    stops: &[
+      rgb(68.0, 1.0, 84.0),
+      rgb(59.0, 82.0, 139.0),
+      rgb(33.0, 145.0, 140.0),
+      rgb(94.0, 201.0, 98.0),
+      rgb(253.0, 231.0, 37.0),
+    ],
+    color_space: ColorSpace::Oklab,
+  },
+  Palette {
+    name: "Rainbow",
+    stops: &[
+      rgb(230.0, 60.0, 60.0),
+      rgb(60.0, 230.0, 60.0),
+      rgb(60.0, 60.0, 230.0),
+    ],
+    color_space: ColorSpace::Hsl,
+  },
+  Palette {
+    name: "Ocean",
+    stops: &[
+      rgb(10.0, 40.0, 90.0),
+      rgb(30.0, 150.0, 190.0),
+      rgb(200.0, 240.0, 230.0),
+    ],
+    color_space: ColorSpace::Oklab,
+  },
+  Palette {
+    name: "Ember",
+    stops: &[
+      rgb(90.0, 20.0, 20.0),
+      rgb(210.0, 70.0, 30.0),
+      rgb(250.0, 200.0, 70.0),
+    ],
+    color_space: ColorSpace::Oklab,
+  },
+  // Okabe and Ito's colors, told apart with any kind of color vision.
+  Palette {
+    name: "Colorblind Safe",
+    stops: &[
+      rgb(230.0, 159.0, 0.0),
+      rgb(240.0, 228.0, 66.0),
+      rgb(86.0, 180.0, 233.0),
+      rgb(0.0, 114.0, 178.0),
+    ],
+    color_space: ColorSpace::Rgb,
+  },
+];
+
+/// The heat map's ramp, from cool to hot.
+pub const HEAT_RAMP: Palette = Palette {
+  name: "Heat",
+  stops: &[
+    rgb(40.0, 40.0, 160.0),
+    rgb(40.0, 180.0, 120.0),
+    rgb(250.0, 220.0, 60.0),
+    rgb(230.0, 40.0, 40.0),
+  ],
+  color_space: ColorSpace::Rgb,
+};
+
+/// Colors along evenly spaced stops. Progress below 0 or above 1 gets the
+/// first or the last stop.
 pub struct GradientManager {
-  start_color: Color,
-  end_color: Color,
+  stops: Vec<Color>,
+  color_space: ColorSpace,
 }
 
 impl GradientManager {
   pub fn new(start_color: Color, end_color: Color) -> GradientManager {
-    GradientManager {
-      start_color,
-      end_color,
-    }
+    GradientManager::with_stops(vec![start_color, end_color], ColorSpace::Rgb)
+  }
+
+  pub fn with_stops(stops: Vec<Color>, color_space: ColorSpace) -> GradientManager {
+    GradientManager { stops, color_space }
   }
 
   pub fn interpolate_colors(&self, progress_percentage: f32) -> Color {
-    let red_diff = self.start_color.red - self.end_color.red;
-    let green_diff = self.start_color.green - self.end_color.green;
-    let blue_diff = self.start_color.blue - self.end_color.blue;
+    let segments = self.stops.len().saturating_sub(1);
+    if segments == 0 {
+      return self.stops.first().copied().unwrap_or(rgb(0.0, 0.0, 0.0));
+    }
 
-    let red_delta = red_diff * progress_percentage;
-    let green_delta = green_diff * progress_percentage;
-    let blue_delta = blue_diff * progress_percentage;
+    // Clamping keeps NaN as it is, so it is sent to the first stop first.
+    let progress_percentage = if progress_percentage.is_nan() {
+      0.0
+    } else {
+      progress_percentage
+    };
+    let position = progress_percentage.clamp(0.0, 1.0) * segments as f32;
+    let segment = (position as usize).min(segments - 1);
+    let start_color = self.stops[segment];
+    let end_color = self.stops[segment + 1];
+    let progress = position - segment as f32;
 
-    let red = self.start_color.red - red_delta;
-    let green = self.start_color.green - green_delta;
-    let blue = self.start_color.blue - blue_delta;
+    match self.color_space {
+      ColorSpace::Rgb => {
+        let channels = mix(
+          [start_color.red, start_color.green, start_color.blue].map(f64::from),
+          [end_color.red, end_color.green, end_color.blue].map(f64::from),
+          progress as f64,
+        );
+        rgb(channels[0] as f32, channels[1] as f32, channels[2] as f32)
+      }
+      ColorSpace::Hsl => {
+        let (start_hue, start_rest) = to_hsl(start_color);
+        let (end_hue, end_rest) = to_hsl(end_color);
+        // Grays have no hue of their own and take the other stop's.
+        let start_hue = start_hue.or(end_hue).unwrap_or(0.0);
+        let end_hue = end_hue.unwrap_or(start_hue);
+        let hue_turn = (end_hue - start_hue + 540.0) % 360.0 - 180.0;
+        let saturation = start_rest[0] + (end_rest[0] - start_rest[0]) * progress;
+        let lightness = start_rest[1] + (end_rest[1] - start_rest[1]) * progress;
 
-    Color { red, green, blue }
+        from_hsl(
+          (start_hue + hue_turn * progress + 360.0) % 360.0,
+          saturation,
+          lightness,
+        )
+      }
+      ColorSpace::Oklab => from_oklab(mix(
+        to_oklab(start_color),
+        to_oklab(end_color),
+        progress as f64,
+      )),
+    }
   }
 }
 
+fn mix(start: [f64; 3], end: [f64; 3], progress: f64) -> [f64; 3] {
+  [
+    start[0] + (end[0] - start[0]) * progress,
+    start[1] + (end[1] - start[1]) * progress,
+    start[2] + (end[2] - start[2]) * progress,
+  ]
+}
+
+/// The hue in degrees, `None` for grays, then saturation and lightness.
+fn to_hsl(color: Color) -> (Option<f32>, [f32; 2]) {
+  let [red, green, blue] = [color.red / 255.0, color.green / 255.0, color.blue / 255.0];
+  let max = red.max(green).max(blue);
+  let min = red.min(green).min(blue);
+  let lightness = (max + min) / 2.0;
+  let chroma = max - min;
+
+  if chroma <= f32::EPSILON {
+    return (None, [0.0, lightness]);
+  }
+
+  let saturation = chroma / (1.0 - (2.0 * lightness - 1.0).abs());
+  let hue = if max == red {
+    ((green - blue) / chroma).rem_euclid(6.0)
+  } else if max == green {
+    (blue - red) / chroma + 2.0
+  } else {
+    (red - green) / chroma + 4.0
+  };
+
+  (Some(hue * 60.0), [saturation, lightness])
+}
+
+fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+  let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+  let sector = hue / 60.0;
+  let second = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+  let [red, green, blue] = match sector as u32 {
+    0 => [chroma, second, 0.0],
+    1 => [second, chroma, 0.0],
+    2 => [0.0, chroma, second],
+    3 => [0.0, second, chroma],
+    4 => [second, 0.0, chroma],
+    _ => [chroma, 0.0, second],
+  };
+  let lift = lightness - chroma / 2.0;
+
+  rgb(
+    (red + lift) * 255.0,
+    (green + lift) * 255.0,
+    (blue + lift) * 255.0,
+  )
+}
+
+fn to_oklab(color: Color) -> [f64; 3] {
+  let linear = |channel: f32| {
+    let channel = channel as f64 / 255.0;
+    if channel <= 0.04045 {
+      channel / 12.92
+    } else {
+      ((channel + 0.055) / 1.055).powf(2.4)
+    }
+  };
+  let [red, green, blue] = [linear(color.red), linear(color.green), linear(color.blue)];
+
+  let long = (0.4122214708 * red + 0.5363325363 * green + 0.0514459929 * blue).cbrt();
+  let medium = (0.2119034982 * red + 0.6806995451 * green + 0.1073969566 * blue).cbrt();
+  let short = (0.0883024619 * red + 0.2817188376 * green + 0.6299787005 * blue).cbrt();
+
+  [
+    0.2104542553 * long + 0.7936177850 * medium - 0.0040720468 * short,
+    1.9779984951 * long - 2.4285922050 * medium + 0.4505937099 * short,
+    0.0259040371 * long + 0.7827717662 * medium - 0.8086757660 * short,
+  ]
+}
+
+fn from_oklab([lightness, a, b]: [f64; 3]) -> Color {
+  let long = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+  let medium = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+  let short = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+  let gamma = |channel: f64| {
+    let channel = if channel <= 0.0031308 {
+      12.92 * channel
+    } else {
+      1.055 * channel.powf(1.0 / 2.4) - 0.055
+    };
+    (channel.clamp(0.0, 1.0) * 255.0) as f32
+  };
+
+  rgb(
+    gamma(4.0767416621 * long - 3.3077115913 * medium + 0.2309699292 * short),
+    gamma(-1.2684380046 * long + 2.6097574011 * medium - 0.3413193965 * short),
+    gamma(-0.0041960863 * long - 0.7034186147 * medium + 1.7076147010 * short),
+  )
+}
+
 #[cfg(test)]
 mod testing {
 
   use super::*;
 
+  fn assert_close(actual: Color, expected: Color) {
+    let distance = (actual.red - expected.red).abs()
+      + (actual.green - expected.green).abs()
+      + (actual.blue - expected.blue).abs();
+    assert!(distance < 1.0, "{:?} is not {:?}", actual, expected);
+  }
+
   #[test]
   fn test_color_difference() {
     let _ = SimpleLogger::init(LevelFilter::Info, Config::default());
@@ -74,4 +323,63 @@ mod testing {
 
     assert_ne!(gradient_at_39.red, gradient_at_40.red);
   }
+
+  #[test]
+  fn test_stops_and_clamping() {
+    let stops = vec![HEAT_RAMP.stops[0], HEAT_RAMP.stops[1], HEAT_RAMP.stops[3]];
+    let gradient_manager = GradientManager::with_stops(stops, ColorSpace::Rgb);
+
+    assert_eq!(gradient_manager.interpolate_colors(0.25).green, 110.0);
+    assert_eq!(gradient_manager.interpolate_colors(0.5), HEAT_RAMP.stops[1]);
+    assert_eq!(
+      gradient_manager.interpolate_colors(1.76),
+      HEAT_RAMP.stops[3]
+    );
+    assert_eq!(
+      gradient_manager.interpolate_colors(-3.0),
+      HEAT_RAMP.stops[0]
+    );
+    assert_eq!(
+      gradient_manager.interpolate_colors(f32::NAN),
+      HEAT_RAMP.stops[0]
+    );
+
+    let single = GradientManager::with_stops(vec![HEAT_RAMP.stops[2]], ColorSpace::Oklab);
+    assert_eq!(single.interpolate_colors(0.5), HEAT_RAMP.stops[2]);
+  }
+
+  #[test]
+  fn test_color_spaces() {
+    let red = rgb(255.0, 0.0, 0.0);
+    let blue = rgb(0.0, 0.0, 255.0);
+
+    for &color_space in [ColorSpace::Rgb, ColorSpace::Hsl, ColorSpace::Oklab].iter() {
+      let gradient_manager = GradientManager::with_stops(vec![red, blue], color_space);
+      assert_close(gradient_manager.interpolate_colors(0.0), red);
+      assert_close(gradient_manager.interpolate_colors(1.0), blue);
+    }
+
+    // Red to blue goes the short way around the wheel, through magenta.
+    let hsl = GradientManager::with_stops(vec![red, blue], ColorSpace::Hsl);
+    assert_close(hsl.interpolate_colors(0.5), rgb(255.0, 0.0, 255.0));
+
+    let gray = rgb(128.0, 128.0, 128.0);
+    let to_gray = GradientManager::with_stops(vec![red, gray], ColorSpace::Hsl);
+    assert_eq!(
+      to_gray.interpolate_colors(0.5).green,
+      to_gray.interpolate_colors(0.5).blue
+    );
+
+    for palette in PALETTES.iter() {
+      for stop in palette.stops {
+        assert_close(from_oklab(to_oklab(*stop)), *stop);
+      }
+    }
+  }
+
+  #[test]
+  fn test_palette_named() {
+    assert_eq!(Palette::named("Ocean").name, "Ocean");
+    assert_eq!(Palette::named("Missing"), PALETTES[0]);
+  }
 }
@@ -0,0 +1,108 @@
+use crate::utils::colors::*;
+
+/// The colors of the board and of the page around it. The page takes its
+/// colors from CSS variables, set from `css_variables`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Theme {
+  pub name: &'static str,
+  /// Behind the cellules, and what dying cellules fade into.
+  pub background: Color,
+  /// Lines between the cellules, `None` to leave them out.
+  pub grid_lines: Option<Color>,
+  /// The cellule colors, unless the player picked a palette of their own.
+  pub palette: Palette,
+  /// The halos around alive neighbors, `None` to draw them in the
+  /// cellule's own color.
+  pub halo: Option<Color>,
+  pub page_background: Color,
+  pub surface: Color,
+  pub text: Color,
+}
+
+pub const LIGHT: Theme = Theme {
+  name: "Light",
+  background: rgb(170.0, 170.0, 221.0),
+  grid_lines: None,
+  palette: PALETTES[0],
+  halo: None,
+  page_background: rgb(246.0, 157.0, 60.0),
+  surface: rgb(245.0, 245.0, 245.0),
+  text: rgb(51.0, 51.0, 51.0),
+};
+
+pub const DARK: Theme = Theme {
+  name: "Dark",
+  background: rgb(30.0, 32.0, 48.0),
+  grid_lines: Some(rgb(44.0, 47.0, 68.0)),
+  palette: PALETTES[0],
+  halo: None,
+  page_background: rgb(20.0, 21.0, 30.0),
+  surface: rgb(40.0, 42.0, 58.0),
+  text: rgb(225.0, 225.0, 235.0),
+};
+
+/// Black and white around colors told apart with any kind of color vision.
+pub const HIGH_CONTRAST: Theme = Theme {
+  name: "High Contrast",
+  background: rgb(0.0, 0.0, 0.0),
+  grid_lines: Some(rgb(110.0, 110.0, 110.0)),
+  palette: PALETTES[5],
+  halo: Some(rgb(255.0, 255.0, 255.0)),
+  page_background: rgb(0.0, 0.0, 0.0),
+  surface: rgb(20.0, 20.0, 20.0),
+  text: rgb(255.0, 255.0, 255.0),
+};
+
+pub const THEMES: [Theme; 3] = [LIGHT, DARK, HIGH_CONTRAST];
+
+impl Theme {
+  pub fn named(name: &str) -> Option<Theme> {
+    THEMES.iter().find(|theme| theme.name == name).copied()
+  }
+
+  /// The chosen theme, or the one matching the system's color scheme when
+  /// there is none.
+  pub fn resolve(name: Option<&str>, prefers_dark: bool) -> Theme {
+    match name.and_then(Theme::named) {
+      Some(theme) => theme,
+      None if prefers_dark => DARK,
+      None => LIGHT,
+    }
+  }
+
+  /// CSS variables for the page, as names and values.
+  pub fn css_variables(&self) -> Vec<(&'static str, String)> {
+    vec![
+      ("--board-background", css_color(self.background)),
+      ("--page-background", css_color(self.page_background)),
+      ("--surface-color", css_color(self.surface)),
+      ("--text-color", css_color(self.text)),
+    ]
+  }
+}
+
+pub fn css_color(color: Color) -> String {
+  format!("rgb({}, {}, {})", color.red, color.green, color.blue)
+}
+
+#[cfg(test)]
+mod testing {
+
+  use super::*;
+
+  #[test]
+  fn test_resolve() {
+    let cases = vec![
+      // (chosen theme, prefers dark, expected)
+      (None, false, "Light"),
+      (None, true, "Dark"),
+      (Some("High Contrast"), false, "High Contrast"),
+      (Some("Light"), true, "Light"),
+      (Some("Missing"), true, "Dark"),
+    ];
+
+    for (name, prefers_dark, expected) in cases {
+      assert_eq!(Theme::resolve(name, prefers_dark).name, expected);
+    }
+  }
+}
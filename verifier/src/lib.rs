@@ -0,0 +1,785 @@
+//! Checks submitted runs by playing them again.
+//!
+//! A submission only claims a seed, a list of edits and how the run ended.
+//! `verify` rebuilds the seed, applies the edits, runs the same engine the
+//! client ran until the run ends the same way the client ends it, and
+//! compares the outcome with the claim.
+//!
+//! Runs also carry hash chain checkpoints along the way. `verify` checks all
+//! of them; `spot_check` replays only a few stretches between checkpoints,
+//! which is enough to catch a tampered board on those stretches.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use cellule_life_engine::daily::DailyChallenge;
+use cellule_life_engine::generations::GenerationsLife;
+use cellule_life_engine::geometry::Geometry;
+use cellule_life_engine::ltl::LargerThanLife;
+use cellule_life_engine::rules::{deserialize_states, serialize_states};
+use cellule_life_engine::sparse::SparseLife;
+use cellule_life_engine::{sync_game_state, Engine, MultiStateEngine, Topology, MAX_STEP_COUNT};
+use cellule_life_schema::{
+    Checkpoint, GridModification, HashChain, SendResultPayload, CHECKPOINT_INTERVAL,
+};
+use game_of_life_core::core::game::GameState;
+use game_of_life_core::core::history::History;
+
+/// How a run actually ended when played again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub step_count: i32,
+    pub active_count: i32,
+    pub cellules: String,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// Why a submission was rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rejection {
+    UnknownSeed(String),
+    BoardSize {
+        width: usize,
+        height: usize,
+    },
+    InvalidModification(i32),
+    /// An edit before the start or after the end of the run.
+    ModificationStep(i32),
+    /// A Daily Challenge run on another rule, topology or geometry.
+    ChallengeSettings,
+    EditBudget {
+        budget: usize,
+        used: usize,
+    },
+    StepCount {
+        claimed: i32,
+        actual: i32,
+    },
+    ActiveCount {
+        claimed: i32,
+        actual: i32,
+    },
+    Cellules,
+    /// The run does not pass through the checkpoint at this step.
+    Checkpoint(i32),
+    CheckpointLayout,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rejection::UnknownSeed(label) => write!(formatter, "no seed is labelled {:?}", label),
+            Rejection::BoardSize { width, height } => {
+                write!(
+                    formatter,
+                    "the seed does not fit a {}x{} board",
+                    width, height
+                )
+            }
+            Rejection::InvalidModification(index) => {
+                write!(formatter, "edit at {} is off the board", index)
+            }
+            Rejection::ModificationStep(step_index) => {
+                write!(formatter, "edit at step {} is outside the run", step_index)
+            }
+            Rejection::ChallengeSettings => {
+                write!(
+                    formatter,
+                    "the daily challenge is plain Life on the bounded square board"
+                )
+            }
+            Rejection::EditBudget { budget, used } => {
+                write!(
+                    formatter,
+                    "used {} edits but only {} are allowed",
+                    used, budget
+                )
+            }
+            Rejection::StepCount { claimed, actual } => write!(
+                formatter,
+                "claimed {} steps but the run lasts {}",
+                claimed, actual
+            ),
+            Rejection::ActiveCount { claimed, actual } => write!(
+                formatter,
+                "claimed {} active cellules but the run ends with {}",
+                claimed, actual
+            ),
+            Rejection::Cellules => write!(formatter, "the final cellules do not match"),
+            Rejection::Checkpoint(step_index) => {
+                write!(
+                    formatter,
+                    "the run does not pass the checkpoint at step {}",
+                    step_index
+                )
+            }
+            Rejection::CheckpointLayout => write!(
+                formatter,
+                "checkpoints must fall every {} steps and on the last one",
+                CHECKPOINT_INTERVAL
+            ),
+        }
+    }
+}
+
+/// Accepts the submission if playing it again ends exactly as claimed and,
+/// when the run carries checkpoints, passes through every one of them.
+pub fn verify(payload: &SendResultPayload) -> Result<Replay, Rejection> {
+    let replay = replay(payload)?;
+
+    if replay.step_count != payload.step_count {
+        return Err(Rejection::StepCount {
+            claimed: payload.step_count,
+            actual: replay.step_count,
+        });
+    }
+    if replay.active_count != payload.active_count {
+        return Err(Rejection::ActiveCount {
+            claimed: payload.active_count,
+            actual: replay.active_count,
+        });
+    }
+    if replay.cellules != payload.game_state.cellules {
+        return Err(Rejection::Cellules);
+    }
+
+    // Runs from before checkpoints existed have none to compare.
+    if !payload.checkpoints.is_empty() {
+        let mismatch = payload
+            .checkpoints
+            .iter()
+            .zip(replay.checkpoints.iter())
+            .find(|(claimed, actual)| claimed != actual);
+        if let Some((claimed, _)) = mismatch {
+            return Err(Rejection::Checkpoint(claimed.step_index));
+        }
+        if payload.checkpoints.len() != replay.checkpoints.len() {
+            return Err(Rejection::CheckpointLayout);
+        }
+    }
+
+    Ok(replay)
+}
+
+/// Plays the submitted run again, picking the engine the client picks for
+/// its rule, geometry and topology.
+pub fn replay(payload: &SendResultPayload) -> Result<Replay, Rejection> {
+    let game_state = initial_game_state(payload)?;
+    let mut run = Run::new(payload, game_state);
+    let mut chain = HashChain::new(&run.states());
+
+    // The client refreshes its active count after every step except the one
+    // that ends the run, so the claimed count is the one before it.
+    let mut active_count = run.active_count() as i32;
+    let mut step_count = 0;
+
+    loop {
+        // Edits made while paused take effect before the next step.
+        if step_count > 0 && run.apply_edits(&payload.modifications, step_count) {
+            active_count = run.active_count() as i32;
+        }
+
+        step_count += 1;
+
+        let in_endless_loop = run.step();
+        chain.push(&run.states());
+
+        if in_endless_loop || step_count > MAX_STEP_COUNT {
+            break;
+        }
+
+        active_count = run.active_count() as i32;
+    }
+
+    if let Some(late) = late_modification(payload, step_count) {
+        return Err(Rejection::ModificationStep(late.step_index));
+    }
+
+    let states = run.states();
+    chain.finish(&states);
+
+    Ok(Replay {
+        step_count,
+        active_count,
+        cellules: serialize_states(&states),
+        checkpoints: chain.checkpoints().to_vec(),
+    })
+}
+
+/// How many stretches between checkpoints a run has, the first one starting
+/// from the seed. Runs without checkpoints have none to spot-check.
+pub fn segment_count(payload: &SendResultPayload) -> usize {
+    payload.checkpoints.len()
+}
+
+/// Replays only the given stretches between checkpoints, each from the
+/// checkpoint before it, and checks that it reaches the next checkpoint.
+///
+/// This catches any tampered board along the checked stretches without
+/// replaying the rest of the run. It cannot tell whether the run really ended
+/// where claimed, which needs every earlier board; `verify` checks that.
+/// Runs without checkpoints, and runs on the infinite plane, whose boards
+/// leave out whatever is off screen, are verified in full instead.
+pub fn spot_check(payload: &SendResultPayload, segments: &[usize]) -> Result<(), Rejection> {
+    if payload.checkpoints.is_empty() || payload.topology == Topology::Infinite {
+        return verify(payload).map(|_| ());
+    }
+
+    check_layout(payload)?;
+    let game_state = initial_game_state(payload)?;
+    if let Some(late) = late_modification(payload, payload.step_count) {
+        return Err(Rejection::ModificationStep(late.step_index));
+    }
+
+    for &segment in segments {
+        let end = match payload.checkpoints.get(segment) {
+            Some(end) => end,
+            None => continue,
+        };
+
+        let (mut run, mut chain) = if segment == 0 {
+            let run = Run::new(payload, game_state.clone());
+            let chain = HashChain::new(&run.states());
+            (run, chain)
+        } else {
+            let start = &payload.checkpoints[segment - 1];
+            let states = deserialize_states(&start.cellules)
+                .filter(|states| states.len() == game_state.cellules.len());
+
+            match (states, HashChain::resume(start)) {
+                (Some(states), Some(chain)) => {
+                    (Run::resume(payload, game_state.clone(), states), chain)
+                }
+                _ => return Err(Rejection::Checkpoint(start.step_index)),
+            }
+        };
+
+        // As in `replay`, the claimed count is the one before the last step.
+        let mut active_count = run.active_count() as i32;
+        while chain.step_index() < end.step_index {
+            if chain.step_index() > 0 {
+                run.apply_edits(&payload.modifications, chain.step_index());
+            }
+            active_count = run.active_count() as i32;
+            run.advance();
+            chain.push(&run.states());
+        }
+
+        if chain.hash() != end.hash || serialize_states(&run.states()) != end.cellules {
+            return Err(Rejection::Checkpoint(end.step_index));
+        }
+        if end.step_index == payload.step_count && active_count != payload.active_count {
+            return Err(Rejection::ActiveCount {
+                claimed: payload.active_count,
+                actual: active_count,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// An edit made at or after the step the run ended on, which never took effect.
+fn late_modification(payload: &SendResultPayload, step_count: i32) -> Option<&GridModification> {
+    payload
+        .modifications
+        .iter()
+        .find(|modification| modification.step_index >= step_count)
+}
+
+/// Checkpoints must fall every `CHECKPOINT_INTERVAL` steps and on the last
+/// one, which must hold the claimed final board.
+fn check_layout(payload: &SendResultPayload) -> Result<(), Rejection> {
+    let last = payload
+        .checkpoints
+        .last()
+        .ok_or(Rejection::CheckpointLayout)?;
+    if last.step_index != payload.step_count {
+        return Err(Rejection::CheckpointLayout);
+    }
+    if last.cellules != payload.game_state.cellules {
+        return Err(Rejection::Cellules);
+    }
+
+    let count = payload.checkpoints.len() as i32;
+    let is_evenly_spaced = payload
+        .checkpoints
+        .iter()
+        .zip(1..count)
+        .all(|(checkpoint, number)| checkpoint.step_index == number * CHECKPOINT_INTERVAL);
+    let is_last_in_place = (count - 1) * CHECKPOINT_INTERVAL < last.step_index
+        && last.step_index <= count * CHECKPOINT_INTERVAL;
+
+    if is_evenly_spaced && is_last_in_place {
+        Ok(())
+    } else {
+        Err(Rejection::CheckpointLayout)
+    }
+}
+
+/// The engine the client runs for a payload's rule, geometry and topology.
+struct Run {
+    game_state: GameState,
+    engine: Option<Box<dyn MultiStateEngine>>,
+    sparse: Option<SparseLife>,
+    history: History,
+}
+
+impl Run {
+    fn new(payload: &SendResultPayload, game_state: GameState) -> Run {
+        let mut engine: Option<Box<dyn MultiStateEngine>> = None;
+        let mut sparse = None;
+
+        if payload.rule.is_larger_than_life() {
+            engine = Some(Box::new(LargerThanLife::from_game_state(
+                payload.rule.clone(),
+                &game_state,
+            )));
+        } else if !payload.rule.is_conway() || payload.geometry != Geometry::Square {
+            engine = Some(Box::new(GenerationsLife::from_game_state(
+                payload.rule.clone(),
+                payload.geometry,
+                &game_state,
+            )));
+        } else if payload.topology == Topology::Infinite {
+            sparse = Some(SparseLife::from_game_state(&game_state));
+        }
+
+        Run {
+            game_state,
+            engine,
+            sparse,
+            history: History {
+                previous_steps: vec![],
+            },
+        }
+    }
+
+    /// Picks the run up from a board on the bounded plane, dying states included.
+    fn resume(payload: &SendResultPayload, mut game_state: GameState, states: Vec<u8>) -> Run {
+        let width = game_state.cellules_width;
+        let height = game_state.cellules_height;
+        let alive: Vec<bool> = states.iter().map(|state| *state == 1).collect();
+        sync_game_state(&mut game_state, &alive);
+        let mut run = Run::new(payload, game_state);
+
+        if payload.rule.is_larger_than_life() {
+            run.engine = Some(Box::new(LargerThanLife::from_states(
+                payload.rule.clone(),
+                width,
+                height,
+                states,
+            )));
+        } else if run.engine.is_some() {
+            run.engine = Some(Box::new(GenerationsLife::from_states(
+                payload.rule.clone(),
+                payload.geometry,
+                width,
+                height,
+                states,
+            )));
+        }
+
+        run
+    }
+
+    /// Steps once and reports whether the run has entered an endless loop.
+    fn step(&mut self) -> bool {
+        if let Some(engine) = self.engine.as_mut() {
+            engine.step();
+            engine.is_in_endless_loop()
+        } else if let Some(sparse) = self.sparse.as_mut() {
+            sparse.step();
+            sparse.is_in_endless_loop()
+        } else {
+            self.game_state.step();
+            self.history
+                .is_in_endless_loop(self.game_state.cellules.clone())
+        }
+    }
+
+    /// Applies the edits made while paused after `step_index` generations and
+    /// reports whether there were any.
+    fn apply_edits(&mut self, modifications: &[GridModification], step_index: i32) -> bool {
+        let mut is_edited = false;
+        for modification in modifications
+            .iter()
+            .filter(|modification| modification.step_index == step_index)
+        {
+            let index = modification.grid_index as usize;
+            match (self.engine.as_mut(), self.sparse.as_mut()) {
+                (Some(engine), _) => engine.toggle_cellule(index),
+                (_, Some(sparse)) => sparse.toggle_cellule(index),
+                _ => {
+                    self.game_state.toggle_cellule(index);
+                    self.history.clear_previous_steps();
+                }
+            }
+            is_edited = true;
+        }
+        is_edited
+    }
+
+    /// Steps once without keeping track of loops.
+    fn advance(&mut self) {
+        match (self.engine.as_mut(), self.sparse.as_mut()) {
+            (Some(engine), _) => engine.step(),
+            (_, Some(sparse)) => sparse.step(),
+            _ => self.game_state.step(),
+        }
+    }
+
+    /// The alive cellules as the client scores them: on the infinite plane,
+    /// only those on the submitted board.
+    fn active_count(&self) -> usize {
+        match (self.engine.as_ref(), self.sparse.as_ref()) {
+            (Some(engine), _) => engine.population(),
+            (_, Some(sparse)) => sparse.population_on_board(),
+            _ => Engine::population(&self.game_state),
+        }
+    }
+
+    /// The board as the client hashes and submits it.
+    fn states(&self) -> Vec<u8> {
+        match (self.engine.as_ref(), self.sparse.as_ref()) {
+            (Some(engine), _) => engine.states().to_vec(),
+            (_, Some(sparse)) => alive_states(&sparse.alive_cellules()),
+            _ => alive_states(&Engine::alive_cellules(&self.game_state)),
+        }
+    }
+}
+
+/// The claimed seed on the claimed board, with the edits made before the
+/// start applied.
+fn initial_game_state(payload: &SendResultPayload) -> Result<GameState, Rejection> {
+    if let Some(budget) = payload.edit_budget {
+        if payload.modifications.len() > budget {
+            return Err(Rejection::EditBudget {
+                budget,
+                used: payload.modifications.len(),
+            });
+        }
+    }
+
+    if let Some(daily) = DailyChallenge::parse(&payload.seed_label) {
+        if payload.rule != daily.rule()
+            || payload.topology != daily.topology()
+            || payload.geometry != daily.geometry()
+        {
+            return Err(Rejection::ChallengeSettings);
+        }
+        if payload.modifications.len() > daily.edit_budget() {
+            return Err(Rejection::EditBudget {
+                budget: daily.edit_budget(),
+                used: payload.modifications.len(),
+            });
+        }
+    }
+
+    let seed = payload
+        .geometry
+        .seed(&payload.seed_label)
+        .ok_or_else(|| Rejection::UnknownSeed(payload.seed_label.clone()))?;
+
+    let width = payload.game_state.cellules_width;
+    let height = payload.game_state.cellules_height;
+    if seed.cellules.len() != width * height {
+        return Err(Rejection::BoardSize { width, height });
+    }
+
+    let mut game_state = GameState {
+        active: false,
+        cellules: seed.cellules,
+        cellules_width: width,
+        cellules_height: height,
+        cellule_neighbors: HashMap::new(),
+    };
+
+    for modification in payload.modifications.iter() {
+        let index = modification.grid_index;
+        if index < 0 || index as usize >= width * height {
+            return Err(Rejection::InvalidModification(index));
+        }
+        if modification.step_index < 0 {
+            return Err(Rejection::ModificationStep(modification.step_index));
+        }
+        // Later edits are made mid-run, when the replay gets there.
+        if modification.step_index == 0 {
+            game_state.toggle_cellule(index as usize);
+        }
+    }
+
+    Ok(game_state)
+}
+
+fn alive_states(alive_cellules: &[bool]) -> Vec<u8> {
+    alive_cellules.iter().map(|alive| *alive as u8).collect()
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use cellule_life_engine::rules::Rule;
+    use cellule_life_schema::{SerializedGameState, SCHEMA_VERSION};
+    use game_of_life_core::core::seeds::seeds::get_seeds;
+
+    fn honest_payload(modifications: Vec<GridModification>, rule: Rule) -> SendResultPayload {
+        let mut payload = SendResultPayload {
+            schema_version: SCHEMA_VERSION,
+            game_state: SerializedGameState {
+                cellules: String::new(),
+                active: false,
+                cellules_width: 50,
+                cellules_height: 40,
+            },
+            step_count: 0,
+            active_count: 0,
+            modifications,
+            seed_label: get_seeds()[0].label.clone(),
+            user_name: "ABCD".to_owned(),
+            topology: Topology::Bounded,
+            rule,
+            geometry: Geometry::Square,
+            checkpoints: vec![],
+            edit_budget: None,
+        };
+
+        let replay = replay(&payload).unwrap();
+        payload.step_count = replay.step_count;
+        payload.active_count = replay.active_count;
+        payload.game_state.cellules = replay.cellules;
+        payload.checkpoints = replay.checkpoints;
+        payload
+    }
+
+    fn edits() -> Vec<GridModification> {
+        vec![
+            GridModification {
+                step_index: 0,
+                grid_index: 20 * 50 + 25,
+            },
+            GridModification {
+                step_index: 0,
+                grid_index: 3,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_honest_runs_are_accepted() {
+        assert!(verify(&honest_payload(vec![], Rule::conway())).is_ok());
+        assert!(verify(&honest_payload(edits(), Rule::conway())).is_ok());
+        assert!(verify(&honest_payload(edits(), Rule::parse("B2/S345/C4").unwrap())).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_runs_are_rejected() {
+        let payload = honest_payload(edits(), Rule::conway());
+
+        let mut longer = payload.clone();
+        longer.step_count += 1;
+        assert_eq!(
+            verify(&longer),
+            Err(Rejection::StepCount {
+                claimed: payload.step_count + 1,
+                actual: payload.step_count
+            })
+        );
+
+        let mut emptier = payload.clone();
+        emptier.active_count -= 1;
+        assert!(matches!(
+            verify(&emptier),
+            Err(Rejection::ActiveCount { .. })
+        ));
+
+        let mut different_board = payload.clone();
+        different_board.game_state.cellules = different_board.game_state.cellules.replace('1', "0");
+        assert_eq!(verify(&different_board), Err(Rejection::Cellules));
+    }
+
+    #[test]
+    fn test_impossible_runs_are_rejected() {
+        let mut unknown_seed = honest_payload(vec![], Rule::conway());
+        unknown_seed.seed_label = "No Such Seed".to_owned();
+        assert_eq!(
+            verify(&unknown_seed),
+            Err(Rejection::UnknownSeed("No Such Seed".to_owned()))
+        );
+
+        let mut off_board = honest_payload(vec![], Rule::conway());
+        off_board.modifications.push(GridModification {
+            step_index: 0,
+            grid_index: 50 * 40,
+        });
+        assert_eq!(
+            verify(&off_board),
+            Err(Rejection::InvalidModification(2000))
+        );
+
+        let mut after_the_end = honest_payload(vec![], Rule::conway());
+        after_the_end.modifications.push(GridModification {
+            step_index: after_the_end.step_count,
+            grid_index: 5,
+        });
+        assert_eq!(
+            verify(&after_the_end),
+            Err(Rejection::ModificationStep(after_the_end.step_count))
+        );
+    }
+
+    #[test]
+    fn test_infinite_runs_only_score_the_submitted_board() {
+        let mut payload = honest_payload(vec![], Rule::conway());
+        payload.topology = Topology::Infinite;
+        let mut run = Run::new(&payload, initial_game_state(&payload).unwrap());
+
+        let mut glider = vec![false; 50 * 40];
+        for &(x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter() {
+            glider[y * 50 + x] = true;
+        }
+        run.sparse = Some(SparseLife::new(50, 40, &glider));
+        assert_eq!(run.active_count(), 5);
+
+        // The glider flies off the board, out of the submitted cellules.
+        for _ in 0..400 {
+            run.advance();
+        }
+        assert_eq!(run.active_count(), 0);
+        assert_eq!(run.sparse.as_ref().unwrap().population(), 5);
+        assert!(serialize_states(&run.states())
+            .chars()
+            .all(|state| state == '0'));
+    }
+
+    #[test]
+    fn test_mid_run_edits_apply_at_their_step() {
+        // A blinker in the top left corner, drawn after three steps.
+        let mut mid_run = edits();
+        mid_run.extend((51..54).map(|grid_index| GridModification {
+            step_index: 3,
+            grid_index,
+        }));
+
+        for rule in [Rule::conway(), Rule::parse("B2/S345/C4").unwrap()] {
+            let payload = honest_payload(mid_run.clone(), rule);
+            assert!(verify(&payload).is_ok());
+            assert_eq!(spot_check(&payload, &all_segments(&payload)), Ok(()));
+
+            // The same edits a step later make for a different run.
+            let mut later = payload.clone();
+            for modification in later.modifications[2..].iter_mut() {
+                modification.step_index = 4;
+            }
+            assert!(verify(&later).is_err());
+        }
+    }
+
+    fn all_segments(payload: &SendResultPayload) -> Vec<usize> {
+        (0..segment_count(payload)).collect()
+    }
+
+    #[test]
+    fn test_checkpoints_are_recorded_along_the_run() {
+        let payload = honest_payload(edits(), Rule::conway());
+        let last = payload.checkpoints.last().unwrap();
+
+        assert_eq!(last.step_index, payload.step_count);
+        assert_eq!(last.cellules, payload.game_state.cellules);
+        assert_eq!(
+            payload.checkpoints.len() as i32,
+            (payload.step_count + CHECKPOINT_INTERVAL - 1) / CHECKPOINT_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_spot_checks_accept_honest_runs() {
+        for rule in [Rule::conway(), Rule::parse("B2/S345/C4").unwrap()] {
+            let payload = honest_payload(edits(), rule);
+            assert_eq!(spot_check(&payload, &all_segments(&payload)), Ok(()));
+        }
+
+        // Runs from before checkpoints are replayed in full instead.
+        let mut unhashed = honest_payload(vec![], Rule::conway());
+        unhashed.checkpoints = vec![];
+        assert_eq!(spot_check(&unhashed, &[0]), Ok(()));
+    }
+
+    #[test]
+    fn test_tampered_checkpoints_are_rejected() {
+        let payload = honest_payload(edits(), Rule::conway());
+
+        let mut rehashed = payload.clone();
+        rehashed.checkpoints[0].hash = "0123456789abcdef".to_owned();
+        let step_index = rehashed.checkpoints[0].step_index;
+        assert_eq!(verify(&rehashed), Err(Rejection::Checkpoint(step_index)));
+        assert_eq!(
+            spot_check(&rehashed, &[0]),
+            Err(Rejection::Checkpoint(step_index))
+        );
+
+        let mut repeated = payload.clone();
+        repeated
+            .checkpoints
+            .push(payload.checkpoints.last().unwrap().clone());
+        assert_eq!(verify(&repeated), Err(Rejection::CheckpointLayout));
+        assert_eq!(
+            spot_check(&repeated, &[0]),
+            Err(Rejection::CheckpointLayout)
+        );
+
+        let mut emptier = payload.clone();
+        emptier.active_count -= 1;
+        let last_segment = segment_count(&emptier) - 1;
+        assert!(matches!(
+            spot_check(&emptier, &[last_segment]),
+            Err(Rejection::ActiveCount { .. })
+        ));
+    }
+
+    #[test]
+    fn test_daily_challenges_and_budgets_are_enforced() {
+        let mut daily = honest_payload(vec![], Rule::conway());
+        daily.seed_label = "Daily 2026-10-18".to_owned();
+        daily.modifications = edits();
+        let replayed = replay(&daily).unwrap();
+        daily.step_count = replayed.step_count;
+        daily.active_count = replayed.active_count;
+        daily.game_state.cellules = replayed.cellules;
+        daily.checkpoints = replayed.checkpoints;
+        assert!(verify(&daily).is_ok());
+
+        let mut over_budget = daily.clone();
+        over_budget.modifications = (0..6)
+            .map(|grid_index| GridModification {
+                step_index: 0,
+                grid_index,
+            })
+            .collect();
+        assert_eq!(
+            verify(&over_budget),
+            Err(Rejection::EditBudget { budget: 5, used: 6 })
+        );
+
+        let mut other_rule = daily.clone();
+        other_rule.rule = Rule::parse("B36/S23").unwrap();
+        assert_eq!(verify(&other_rule), Err(Rejection::ChallengeSettings));
+
+        let mut budgeted = honest_payload(edits(), Rule::conway());
+        budgeted.edit_budget = Some(2);
+        assert!(verify(&budgeted).is_ok());
+        budgeted.edit_budget = Some(1);
+        assert_eq!(
+            verify(&budgeted),
+            Err(Rejection::EditBudget { budget: 1, used: 2 })
+        );
+
+        let mut no_such_day = daily;
+        no_such_day.seed_label = "Daily 2026-02-30".to_owned();
+        assert!(matches!(
+            verify(&no_such_day),
+            Err(Rejection::UnknownSeed(_))
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! Verifies submitted scores in bulk.
+//!
+//! Reads JSON from the files given, or stdin: a single submission, a list of
+//! stored scores (the mock server's store file) or a `get-high-scores`
+//! response. Prints one `accept` or `reject` line per score and exits with
+//! status 1 if anything was rejected.
+//!
+//! With `--spot-check N`, only N randomly picked stretches between each run's
+//! checkpoints are replayed instead of the whole run. `--seed S` picks the
+//! same stretches every time, to reproduce a spot check.
+
+use cellule_life_schema::SendResultPayload;
+use cellule_life_verifier::{segment_count, spot_check, verify};
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::SeedableRng;
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+const USAGE: &str = "usage: cellule-life-verifier [--spot-check N [--seed S]] [FILE...]";
+
+fn main() {
+    let mut paths = vec![];
+    let mut spot_checks = None;
+    let mut seed = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--spot-check" => {
+                let count = args.next().and_then(|value| value.parse::<usize>().ok());
+                spot_checks = Some(count.unwrap_or_else(|| exit(USAGE)));
+            }
+            "--seed" => {
+                let value = args.next().and_then(|value| value.parse::<u64>().ok());
+                seed = Some(value.unwrap_or_else(|| exit(USAGE)));
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    let inputs = if paths.is_empty() {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .unwrap_or_else(|error| exit(&format!("could not read stdin: {}", error)));
+        vec![("stdin".to_owned(), input)]
+    } else {
+        paths
+            .into_iter()
+            .map(|path| {
+                let input = fs::read_to_string(&path)
+                    .unwrap_or_else(|error| exit(&format!("could not read {}: {}", path, error)));
+                (path, input)
+            })
+            .collect()
+    };
+
+    let mut random = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let (mut accepted, mut rejected) = (0, 0);
+    for (source, input) in inputs {
+        let json: Value = serde_json::from_str(&input)
+            .unwrap_or_else(|error| exit(&format!("{} is not JSON: {}", source, error)));
+
+        for (index, score) in scores(json).into_iter().enumerate() {
+            let label = match score.get("_id").and_then(Value::as_str) {
+                Some(id) => id.to_owned(),
+                None => format!("{}#{}", source, index),
+            };
+
+            let verdict = SendResultPayload::from_value(score)
+                .map_err(|error| error.to_string())
+                .and_then(|payload| {
+                    let verdict = match spot_checks {
+                        Some(count) => {
+                            spot_check(&payload, &pick_segments(&mut random, &payload, count))
+                        }
+                        None => verify(&payload).map(|_| ()),
+                    };
+                    verdict.map_err(|rejection| rejection.to_string())
+                });
+
+            match verdict {
+                Ok(_) => {
+                    accepted += 1;
+                    println!("accept {}", label);
+                }
+                Err(reason) => {
+                    rejected += 1;
+                    println!("reject {}: {}", label, reason);
+                }
+            }
+        }
+    }
+
+    eprintln!("{} accepted, {} rejected", accepted, rejected);
+    if rejected > 0 {
+        process::exit(1);
+    }
+}
+
+/// The scores in any of the accepted input shapes.
+fn scores(json: Value) -> Vec<Value> {
+    match json {
+        Value::Array(scores) => scores,
+        Value::Object(mut object) => match object.remove("scores") {
+            Some(Value::Array(scores)) => scores,
+            _ => vec![Value::Object(object)],
+        },
+        other => vec![other],
+    }
+}
+
+/// `count` different segments of the run, picked at random so a submitter
+/// cannot know which stretches will be replayed.
+fn pick_segments(random: &mut StdRng, payload: &SendResultPayload, count: usize) -> Vec<usize> {
+    let segment_count = segment_count(payload);
+    index::sample(random, segment_count, count.min(segment_count)).into_vec()
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2)
+}